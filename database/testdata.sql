@@ -1,42 +1,65 @@
-INSERT INTO account (username, password, phone, gender, join_time, balance, user_type)
-VALUES ('admin', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', NULL, NULL, NULL, NULL, 0);
+INSERT INTO account (username, password, phone, gender, join_time, balance, user_type, role_id)
+VALUES ('admin', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', NULL, NULL, NULL, NULL, 0, 1);
 
-INSERT INTO account (username, password, phone, gender, join_time, balance, user_type)
+INSERT INTO account (username, password, phone, gender, join_time, balance, user_type, role_id)
 VALUES 
-('user1', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139001', 1, '2024-10-05', 100.00, 1),
-('user2', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139002', 0, '2024-12-12', 50.00, 1),
-('user3', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139003', 0, '2025-01-13', 75.50, 1),
-('user4', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139004', 1, '2025-03-22', 200.00, 1),
-('user5', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139005', 0, '2025-05-13', 30.25, 1);
+('user1', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139001', 1, '2024-10-05', 100.00, 1, 4),
+('user2', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139002', 0, '2024-12-12', 50.00, 1, 4),
+('user3', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139003', 0, '2025-01-13', 75.50, 1, 4),
+('user4', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139004', 1, '2025-03-22', 200.00, 1, 4),
+('user5', '$2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK', '13900139005', 0, '2025-05-13', 30.25, 1, 4);
 
 -- password: 123456 ($2b$12$d9n2xF3hslElNuQAqrln/OeEeYRLnMZIOwvnlpqns5uwr92Wq65yK)
 
-INSERT INTO goods (goods_name, goods_type, stock, price) VALUES
-('意式浓缩', '咖啡类', 50, 18.00),
-('拿铁咖啡', '咖啡类', 50, 25.00),
-('卡布奇诺', '咖啡类', 50, 25.00),
-('焦糖玛奇朵', '咖啡类', 50, 28.00),
-('冰美式咖啡', '咖啡类', 50, 22.00),
-('冷萃咖啡', '咖啡类', 30, 30.00),
-('英式早餐茶', '非咖啡饮品', 40, 20.00),
-('抹茶拿铁', '非咖啡饮品', 40, 25.00),
-('热巧克力', '非咖啡饮品', 40, 22.00),
-('草莓奶昔', '非咖啡饮品', 30, 28.00),
-('柠檬蜂蜜水', '非咖啡饮品', 50, 18.00),
-('芝士蛋糕', '烘焙食品', 20, 32.00),
-('提拉米苏', '烘焙食品', 20, 35.00),
-('牛角包', '烘焙食品', 30, 15.00),
-('肉桂卷', '烘焙食品', 25, 18.00),
-('巧克力曲奇', '烘焙食品', 40, 12.00),
-('火腿芝士三明治', '轻食简餐', 25, 28.00),
-('鸡肉牛油果三明治', '轻食简餐', 25, 32.00),
-('凯撒沙拉', '轻食简餐', 20, 30.00),
-('全日早餐', '轻食简餐', 15, 38.00),
-('南瓜汤', '轻食简餐', 30, 22.00),
-('埃塞俄比亚单品豆', '咖啡豆与周边', 15, 120.00),
-('哥伦比亚单品豆', '咖啡豆与周边', 15, 110.00),
-('法压壶', '咖啡豆与周边', 10, 180.00),
-('品牌咖啡杯', '咖啡豆与周边', 20, 65.00);
+-- This file is imported after the migrations have run, so the opening rows
+-- that 0006_balance_ledger backfills are written here instead. Every balance
+-- must equal the sum of its ledger rows.
+INSERT INTO balance_transactions (user_id, txn_type, amount, balance_after, note, created_at)
+SELECT id, 'opening', balance, balance, 'Opening balance from test data', COALESCE(join_time, NOW())
+FROM account
+WHERE balance IS NOT NULL AND balance <> 0;
+
+-- Migration 0017 already seeds these categories; INSERT IGNORE keeps this
+-- file importable either way.
+INSERT IGNORE INTO goods_category (name, display_order) VALUES
+('咖啡类', 1),
+('非咖啡饮品', 2),
+('烘焙食品', 3),
+('轻食简餐', 4),
+('咖啡豆与周边', 5);
+
+SET @coffee = (SELECT id FROM goods_category WHERE name = '咖啡类');
+SET @drinks = (SELECT id FROM goods_category WHERE name = '非咖啡饮品');
+SET @bakery = (SELECT id FROM goods_category WHERE name = '烘焙食品');
+SET @meals = (SELECT id FROM goods_category WHERE name = '轻食简餐');
+SET @retail = (SELECT id FROM goods_category WHERE name = '咖啡豆与周边');
+
+INSERT INTO goods (goods_name, category_id, stock, price) VALUES
+('意式浓缩', @coffee, 50, 18.00),
+('拿铁咖啡', @coffee, 50, 25.00),
+('卡布奇诺', @coffee, 50, 25.00),
+('焦糖玛奇朵', @coffee, 50, 28.00),
+('冰美式咖啡', @coffee, 50, 22.00),
+('冷萃咖啡', @coffee, 30, 30.00),
+('英式早餐茶', @drinks, 40, 20.00),
+('抹茶拿铁', @drinks, 40, 25.00),
+('热巧克力', @drinks, 40, 22.00),
+('草莓奶昔', @drinks, 30, 28.00),
+('柠檬蜂蜜水', @drinks, 50, 18.00),
+('芝士蛋糕', @bakery, 20, 32.00),
+('提拉米苏', @bakery, 20, 35.00),
+('牛角包', @bakery, 30, 15.00),
+('肉桂卷', @bakery, 25, 18.00),
+('巧克力曲奇', @bakery, 40, 12.00),
+('火腿芝士三明治', @meals, 25, 28.00),
+('鸡肉牛油果三明治', @meals, 25, 32.00),
+('凯撒沙拉', @meals, 20, 30.00),
+('全日早餐', @meals, 15, 38.00),
+('南瓜汤', @meals, 30, 22.00),
+('埃塞俄比亚单品豆', @retail, 15, 120.00),
+('哥伦比亚单品豆', @retail, 15, 110.00),
+('法压壶', @retail, 10, 180.00),
+('品牌咖啡杯', @retail, 20, 65.00);
 
 INSERT INTO lost_items (item_name, pick_place, pick_user_id, claim_user_id, pick_time, claim_time, status)
 VALUES
@@ -1,5 +1,5 @@
-CREATE TABLE account (
-    id BIGSERIAL PRIMARY KEY,
+CREATE TABLE IF NOT EXISTS account (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
     username VARCHAR(20) UNIQUE NOT NULL,
     password VARCHAR(255) NOT NULL, -- Increased length for hashed passwords
     phone VARCHAR(50),
@@ -9,8 +9,8 @@ CREATE TABLE account (
     user_type SMALLINT CHECK (user_type IN (0, 1)) -- 0: Staff, 1: Customer
 );
 
-CREATE TABLE lost_items (
-    id BIGSERIAL PRIMARY KEY,
+CREATE TABLE IF NOT EXISTS lost_items (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
     item_name VARCHAR(20) NOT NULL,
     pick_place VARCHAR(20),
     pick_user_id BIGINT,
@@ -22,15 +22,15 @@ CREATE TABLE lost_items (
     FOREIGN KEY (claim_user_id) REFERENCES account (id)
 );
 
-CREATE TABLE goods (
-    id SERIAL PRIMARY KEY,
+CREATE TABLE IF NOT EXISTS goods (
+    id INT AUTO_INCREMENT PRIMARY KEY,
     goods_name VARCHAR(20) NOT NULL,
     goods_type VARCHAR(20),
     stock INT DEFAULT 0,
     price DECIMAL(10, 2) NOT NULL
 );
 
-CREATE TABLE consumption (
+CREATE TABLE IF NOT EXISTS consumption (
     user_id BIGINT NOT NULL,
     month VARCHAR(7) NOT NULL, -- Format 'YYYY-MM'
     goods_id INT NOT NULL,
@@ -40,8 +40,8 @@ CREATE TABLE consumption (
     FOREIGN KEY (goods_id) REFERENCES goods (id)
 );
 
-CREATE TABLE message (
-    id BIGSERIAL PRIMARY KEY,
+CREATE TABLE IF NOT EXISTS message (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
     sender_id BIGINT NOT NULL,
     receiver_id BIGINT NOT NULL,
     title VARCHAR(255),
@@ -50,4 +50,4 @@ CREATE TABLE message (
     read_status SMALLINT CHECK (read_status IN (0, 1)), -- 0: Unread, 1: Read
     FOREIGN KEY (sender_id) REFERENCES account (id),
     FOREIGN KEY (receiver_id) REFERENCES account (id)
-);
\ No newline at end of file
+);
@@ -0,0 +1,58 @@
+CREATE TABLE role (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    role_name VARCHAR(20) UNIQUE NOT NULL,
+    is_staff SMALLINT NOT NULL CHECK (is_staff IN (0, 1)), -- 0: Customer role, 1: Staff role
+    is_builtin SMALLINT NOT NULL DEFAULT 0 CHECK (is_builtin IN (0, 1))
+);
+
+CREATE TABLE role_permission (
+    role_id INT NOT NULL,
+    permission VARCHAR(50) NOT NULL,
+    PRIMARY KEY (role_id, permission),
+    FOREIGN KEY (role_id) REFERENCES role (id) ON DELETE CASCADE
+);
+
+INSERT INTO role (id, role_name, is_staff, is_builtin) VALUES
+(1, 'manager', 1, 1),
+(2, 'cashier', 1, 1),
+(3, 'barista', 1, 1),
+(4, 'customer', 0, 1);
+
+INSERT INTO role_permission (role_id, permission) VALUES
+(1, 'view_analytics'),
+(1, 'view_all_users'),
+(1, 'manage_goods'),
+(1, 'recharge_balance'),
+(1, 'send_staff_messages'),
+(1, 'view_system_status'),
+(1, 'manage_roles'),
+(1, 'view_goods'),
+(1, 'view_lost_items'),
+(1, 'report_lost_item'),
+(1, 'use_messages'),
+(2, 'view_all_users'),
+(2, 'recharge_balance'),
+(2, 'send_staff_messages'),
+(2, 'view_goods'),
+(2, 'view_lost_items'),
+(2, 'report_lost_item'),
+(2, 'use_messages'),
+(3, 'view_goods'),
+(3, 'view_lost_items'),
+(3, 'report_lost_item'),
+(3, 'use_messages'),
+(4, 'manage_own_account'),
+(4, 'purchase'),
+(4, 'claim_lost_item'),
+(4, 'contact_staff'),
+(4, 'view_goods'),
+(4, 'view_lost_items'),
+(4, 'report_lost_item'),
+(4, 'use_messages');
+
+ALTER TABLE account ADD COLUMN role_id INT;
+ALTER TABLE account ADD CONSTRAINT fk_account_role FOREIGN KEY (role_id) REFERENCES role (id);
+
+-- Existing staff become managers so nobody loses access
+UPDATE account SET role_id = 1 WHERE user_type = 0;
+UPDATE account SET role_id = 4 WHERE user_type = 1;
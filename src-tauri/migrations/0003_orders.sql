@@ -0,0 +1,37 @@
+CREATE TABLE IF NOT EXISTS orders (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    total_amount DECIMAL(10, 2) NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES account (id),
+    INDEX idx_orders_user_created (user_id, created_at),
+    INDEX idx_orders_created (created_at)
+);
+
+CREATE TABLE IF NOT EXISTS order_items (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    order_id BIGINT NOT NULL,
+    goods_id INT NOT NULL,
+    quantity INT NOT NULL,
+    unit_price DECIMAL(10, 2) NOT NULL,
+    line_total DECIMAL(10, 2) NOT NULL,
+    FOREIGN KEY (order_id) REFERENCES orders (id) ON DELETE CASCADE,
+    FOREIGN KEY (goods_id) REFERENCES goods (id)
+);
+
+-- Carry the old monthly aggregates over so analytics keep their history.
+-- Those rows never recorded quantities or unit prices, so each month becomes
+-- one order dated the 1st, with one line (quantity 1) per goods.
+INSERT INTO orders (user_id, total_amount, created_at)
+SELECT user_id, SUM(amount), STR_TO_DATE(CONCAT(month, '-01'), '%Y-%m-%d')
+FROM consumption
+GROUP BY user_id, month;
+
+INSERT INTO order_items (order_id, goods_id, quantity, unit_price, line_total)
+SELECT o.id, c.goods_id, 1, c.amount, c.amount
+FROM consumption c
+JOIN orders o
+    ON o.user_id = c.user_id
+    AND o.created_at = STR_TO_DATE(CONCAT(c.month, '-01'), '%Y-%m-%d');
+
+DROP TABLE consumption;
@@ -0,0 +1,17 @@
+ALTER TABLE orders
+    ADD COLUMN subtotal DECIMAL(10, 2) NULL AFTER user_id,
+    ADD COLUMN discount_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER subtotal,
+    ADD COLUMN balance_before DECIMAL(10, 2) NULL AFTER total_amount,
+    ADD COLUMN balance_after DECIMAL(10, 2) NULL AFTER balance_before;
+
+-- Orders carried over from the monthly aggregates had no discounts; their
+-- balances were never recorded and stay NULL.
+UPDATE orders SET subtotal = total_amount WHERE subtotal IS NULL;
+ALTER TABLE orders MODIFY COLUMN subtotal DECIMAL(10, 2) NOT NULL;
+
+ALTER TABLE order_items
+    ADD COLUMN discount_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER unit_price;
+
+INSERT IGNORE INTO role_permission (role_id, permission) VALUES
+(1, 'view_all_orders'),
+(2, 'view_all_orders');
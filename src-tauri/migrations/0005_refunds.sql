@@ -0,0 +1,32 @@
+ALTER TABLE orders
+    ADD COLUMN refunded_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER total_amount,
+    ADD COLUMN status SMALLINT NOT NULL DEFAULT 0 CHECK (status IN (0, 1, 2)); -- 0: Completed, 1: Partially refunded, 2: Refunded
+
+ALTER TABLE order_items
+    ADD COLUMN refunded_quantity INT NOT NULL DEFAULT 0 AFTER quantity,
+    ADD COLUMN refunded_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER line_total;
+
+CREATE TABLE IF NOT EXISTS refunds (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    order_id BIGINT NOT NULL,
+    amount DECIMAL(10, 2) NOT NULL,
+    reason VARCHAR(255) NOT NULL,
+    refunded_by BIGINT NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (order_id) REFERENCES orders (id),
+    FOREIGN KEY (refunded_by) REFERENCES account (id)
+);
+
+CREATE TABLE IF NOT EXISTS refund_items (
+    refund_id BIGINT NOT NULL,
+    order_item_id BIGINT NOT NULL,
+    quantity INT NOT NULL,
+    amount DECIMAL(10, 2) NOT NULL,
+    PRIMARY KEY (refund_id, order_item_id),
+    FOREIGN KEY (refund_id) REFERENCES refunds (id) ON DELETE CASCADE,
+    FOREIGN KEY (order_item_id) REFERENCES order_items (id)
+);
+
+INSERT IGNORE INTO role_permission (role_id, permission) VALUES
+(1, 'refund_orders'),
+(2, 'refund_orders');
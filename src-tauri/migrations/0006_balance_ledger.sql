@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS balance_transactions (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    txn_type VARCHAR(20) NOT NULL, -- opening, recharge, purchase, refund
+    amount DECIMAL(10, 2) NOT NULL, -- Signed: credits are positive, debits negative
+    balance_after DECIMAL(10, 2) NOT NULL,
+    operator_id BIGINT,
+    order_id BIGINT,
+    refund_id BIGINT,
+    note VARCHAR(255),
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES account (id),
+    FOREIGN KEY (operator_id) REFERENCES account (id),
+    FOREIGN KEY (order_id) REFERENCES orders (id),
+    FOREIGN KEY (refund_id) REFERENCES refunds (id),
+    INDEX idx_balance_transactions_user (user_id, created_at)
+);
+
+-- Open the ledger with each account's current balance so it sums correctly
+-- from day one.
+INSERT INTO balance_transactions (user_id, txn_type, amount, balance_after, note, created_at)
+SELECT id, 'opening', balance, balance, 'Balance when the ledger was introduced', NOW()
+FROM account
+WHERE balance IS NOT NULL AND balance <> 0;
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS recharges (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    amount DECIMAL(10, 2) NOT NULL,
+    payment_method VARCHAR(20) NOT NULL, -- cash, card, transfer
+    note VARCHAR(255),
+    operator_id BIGINT NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES account (id),
+    FOREIGN KEY (operator_id) REFERENCES account (id),
+    INDEX idx_recharges_user (user_id, created_at)
+);
+
+ALTER TABLE balance_transactions
+    ADD COLUMN recharge_id BIGINT AFTER refund_id,
+    ADD CONSTRAINT fk_balance_transactions_recharge FOREIGN KEY (recharge_id) REFERENCES recharges (id);
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS recharge_bonus_rules (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(50) NOT NULL,
+    min_amount DECIMAL(10, 2) NOT NULL, -- Smallest top-up the rule applies to
+    bonus_type VARCHAR(10) NOT NULL CHECK (bonus_type IN ('fixed', 'percent')),
+    bonus_value DECIMAL(10, 2) NOT NULL, -- Amount for fixed rules, percentage of the top-up for percent rules
+    valid_from DATE, -- Inclusive, NULL means no start date
+    valid_until DATE, -- Inclusive, NULL means no end date
+    is_active TINYINT NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL
+);
+
+ALTER TABLE recharges
+    ADD COLUMN bonus_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER amount,
+    ADD COLUMN bonus_rule_id BIGINT AFTER bonus_amount,
+    ADD CONSTRAINT fk_recharges_bonus_rule FOREIGN KEY (bonus_rule_id) REFERENCES recharge_bonus_rules (id) ON DELETE SET NULL;
+
+INSERT IGNORE INTO role_permission (role_id, permission) VALUES
+(1, 'manage_promotions');
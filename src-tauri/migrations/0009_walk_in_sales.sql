@@ -0,0 +1,13 @@
+-- Walk-in sales have no customer account. Member orders are paid from the
+-- stored balance; walk-in orders record how they were paid and who rang them up.
+ALTER TABLE orders
+    MODIFY COLUMN user_id BIGINT NULL,
+    ADD COLUMN payment_method VARCHAR(20) NOT NULL DEFAULT 'balance' AFTER total_amount, -- balance, cash, card, transfer
+    ADD COLUMN cash_tendered DECIMAL(10, 2) AFTER payment_method,
+    ADD COLUMN change_due DECIMAL(10, 2) AFTER cash_tendered,
+    ADD COLUMN cashier_id BIGINT AFTER change_due,
+    ADD CONSTRAINT fk_orders_cashier FOREIGN KEY (cashier_id) REFERENCES account (id);
+
+INSERT IGNORE INTO role_permission (role_id, permission) VALUES
+(1, 'walk_in_sales'),
+(2, 'walk_in_sales');
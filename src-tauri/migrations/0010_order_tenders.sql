@@ -0,0 +1,25 @@
+-- Each order is paid by one or more tenders that sum to its total.
+-- orders.payment_method keeps the single method used, or 'split'.
+CREATE TABLE IF NOT EXISTS order_tenders (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    order_id BIGINT NOT NULL,
+    tender_type VARCHAR(20) NOT NULL, -- balance, cash, card, transfer
+    amount DECIMAL(10, 2) NOT NULL,
+    FOREIGN KEY (order_id) REFERENCES orders (id) ON DELETE CASCADE,
+    INDEX idx_order_tenders_order (order_id)
+);
+
+INSERT INTO order_tenders (order_id, tender_type, amount)
+SELECT id, payment_method, total_amount
+FROM orders
+WHERE total_amount > 0;
+
+-- Part of a refund credited back to the member balance. The rest is paid
+-- out at the till. Earlier member refunds all went to the balance.
+ALTER TABLE refunds
+    ADD COLUMN balance_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER amount;
+
+UPDATE refunds r
+JOIN orders o ON r.order_id = o.id
+SET r.balance_amount = r.amount
+WHERE o.user_id IS NOT NULL;
@@ -0,0 +1,34 @@
+CREATE TABLE IF NOT EXISTS coupons (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    code VARCHAR(32) NOT NULL UNIQUE, -- Stored upper-case
+    description VARCHAR(100),
+    discount_type VARCHAR(10) NOT NULL CHECK (discount_type IN ('percent', 'fixed', 'free_item')),
+    discount_value DECIMAL(10, 2), -- Percentage or amount off, NULL for free_item
+    free_goods_id INT, -- One unit of this goods is free, free_item only
+    min_spend DECIMAL(10, 2) NOT NULL DEFAULT 0.00, -- Compared with the order subtotal
+    per_customer_limit INT, -- NULL means unlimited
+    valid_from DATE, -- Inclusive, NULL means no start date
+    valid_until DATE, -- Inclusive, NULL means it never expires
+    is_active TINYINT NOT NULL DEFAULT 1,
+    created_by BIGINT NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (free_goods_id) REFERENCES goods (id),
+    FOREIGN KEY (created_by) REFERENCES account (id)
+);
+
+CREATE TABLE IF NOT EXISTS coupon_redemptions (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    coupon_id BIGINT NOT NULL,
+    order_id BIGINT NOT NULL UNIQUE,
+    user_id BIGINT NOT NULL,
+    discount_amount DECIMAL(10, 2) NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (coupon_id) REFERENCES coupons (id),
+    FOREIGN KEY (order_id) REFERENCES orders (id),
+    FOREIGN KEY (user_id) REFERENCES account (id),
+    INDEX idx_coupon_redemptions_user (coupon_id, user_id)
+);
+
+ALTER TABLE orders
+    ADD COLUMN coupon_id BIGINT AFTER discount_amount,
+    ADD CONSTRAINT fk_orders_coupon FOREIGN KEY (coupon_id) REFERENCES coupons (id);
@@ -0,0 +1,30 @@
+ALTER TABLE account ADD COLUMN points INT NOT NULL DEFAULT 0 AFTER balance;
+
+-- Points needed to redeem one unit for free, NULL if it cannot be redeemed
+ALTER TABLE goods ADD COLUMN points_price INT;
+
+CREATE TABLE IF NOT EXISTS points_transactions (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    txn_type VARCHAR(20) NOT NULL, -- earn, redeem, expire, adjust
+    points INT NOT NULL, -- Signed: credits are positive, debits negative
+    points_after INT NOT NULL,
+    remaining INT, -- Credits only: points of this lot not yet spent or expired
+    expires_at DATE, -- Credits only
+    operator_id BIGINT,
+    order_id BIGINT,
+    note VARCHAR(255),
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES account (id),
+    FOREIGN KEY (operator_id) REFERENCES account (id),
+    FOREIGN KEY (order_id) REFERENCES orders (id),
+    INDEX idx_points_transactions_user (user_id, created_at),
+    INDEX idx_points_transactions_lots (user_id, expires_at)
+);
+
+ALTER TABLE orders
+    ADD COLUMN points_redeemed INT NOT NULL DEFAULT 0 AFTER coupon_id,
+    ADD COLUMN points_earned INT NOT NULL DEFAULT 0 AFTER points_redeemed;
+
+INSERT IGNORE INTO role_permission (role_id, permission) VALUES
+(1, 'adjust_points');
@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS membership_tiers (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(20) NOT NULL UNIQUE,
+    min_spend DECIMAL(10, 2) NOT NULL, -- Net spend over the last 365 days
+    discount_percent DECIMAL(5, 2) NOT NULL DEFAULT 0.00 CHECK (discount_percent BETWEEN 0 AND 100)
+);
+
+INSERT INTO membership_tiers (id, name, min_spend, discount_percent) VALUES
+(1, 'standard', 0.00, 0.00),
+(2, 'silver', 500.00, 2.00),
+(3, 'gold', 2000.00, 5.00),
+(4, 'platinum', 5000.00, 8.00);
+
+ALTER TABLE account
+    ADD COLUMN tier_id INT AFTER points,
+    ADD CONSTRAINT fk_account_tier FOREIGN KEY (tier_id) REFERENCES membership_tiers (id);
+
+ALTER TABLE orders
+    ADD COLUMN tier_discount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER discount_amount; -- Part of discount_amount
+
+UPDATE account SET tier_id = (
+    SELECT t.id FROM membership_tiers t
+    WHERE t.min_spend <= (
+        SELECT COALESCE(SUM(o.total_amount - o.refunded_amount), 0) FROM orders o
+        WHERE o.user_id = account.id AND o.created_at >= NOW() - INTERVAL 365 DAY
+    )
+    ORDER BY t.min_spend DESC, t.id DESC LIMIT 1
+)
+WHERE user_type = 1;
@@ -0,0 +1,45 @@
+CREATE TABLE IF NOT EXISTS gift_cards (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    code VARCHAR(32) NOT NULL UNIQUE, -- Stored upper-case
+    initial_value DECIMAL(10, 2) NOT NULL,
+    balance DECIMAL(10, 2) NOT NULL,
+    note VARCHAR(255),
+    issued_by BIGINT NOT NULL,
+    issued_at DATETIME NOT NULL,
+    FOREIGN KEY (issued_by) REFERENCES account (id)
+);
+
+CREATE TABLE IF NOT EXISTS gift_card_transactions (
+    id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    gift_card_id BIGINT NOT NULL,
+    txn_type VARCHAR(20) NOT NULL, -- issue, load, redeem, refund, transfer
+    amount DECIMAL(10, 2) NOT NULL, -- Signed: credits are positive, debits negative
+    balance_after DECIMAL(10, 2) NOT NULL,
+    payment_method VARCHAR(20), -- How an issue or load was paid for
+    order_id BIGINT,
+    refund_id BIGINT,
+    user_id BIGINT, -- Member who received a transfer
+    operator_id BIGINT,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (gift_card_id) REFERENCES gift_cards (id),
+    FOREIGN KEY (order_id) REFERENCES orders (id),
+    FOREIGN KEY (refund_id) REFERENCES refunds (id),
+    FOREIGN KEY (user_id) REFERENCES account (id),
+    FOREIGN KEY (operator_id) REFERENCES account (id),
+    INDEX idx_gift_card_transactions_card (gift_card_id, created_at)
+);
+
+ALTER TABLE order_tenders
+    ADD COLUMN gift_card_id BIGINT AFTER amount,
+    ADD CONSTRAINT fk_order_tenders_gift_card FOREIGN KEY (gift_card_id) REFERENCES gift_cards (id);
+
+ALTER TABLE refunds
+    ADD COLUMN gift_card_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER balance_amount;
+
+ALTER TABLE balance_transactions
+    ADD COLUMN gift_card_id BIGINT AFTER recharge_id,
+    ADD CONSTRAINT fk_balance_transactions_gift_card FOREIGN KEY (gift_card_id) REFERENCES gift_cards (id);
+
+INSERT IGNORE INTO role_permission (role_id, permission) VALUES
+(1, 'manage_gift_cards'),
+(2, 'manage_gift_cards');
@@ -0,0 +1,26 @@
+-- Single row holding the shop-wide pricing mode. Prices have always been
+-- what the customer pays, so existing shops start tax-inclusive at 0%.
+CREATE TABLE IF NOT EXISTS tax_settings (
+    id TINYINT PRIMARY KEY CHECK (id = 1),
+    prices_include_tax BOOLEAN NOT NULL DEFAULT TRUE,
+    default_rate_percent DECIMAL(5, 2) NOT NULL DEFAULT 0.00 -- For categories without their own rate
+);
+
+INSERT IGNORE INTO tax_settings (id, prices_include_tax, default_rate_percent) VALUES (1, TRUE, 0.00);
+
+CREATE TABLE IF NOT EXISTS tax_rates (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    goods_type VARCHAR(20) NOT NULL UNIQUE, -- Matches goods.goods_type
+    rate_percent DECIMAL(5, 2) NOT NULL
+);
+
+ALTER TABLE order_items
+    ADD COLUMN tax_rate DECIMAL(5, 2) NOT NULL DEFAULT 0.00 AFTER discount_amount,
+    ADD COLUMN tax_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER tax_rate; -- Part of line_total
+
+ALTER TABLE orders
+    ADD COLUMN tax_amount DECIMAL(10, 2) NOT NULL DEFAULT 0.00 AFTER discount_amount, -- Part of total_amount
+    ADD COLUMN prices_include_tax BOOLEAN NOT NULL DEFAULT TRUE AFTER tax_amount;
+
+INSERT IGNORE INTO role_permission (role_id, permission) VALUES
+(1, 'manage_taxes');
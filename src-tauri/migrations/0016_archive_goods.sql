@@ -0,0 +1,4 @@
+-- Archived goods drop off the menu and cannot be sold, but stay in the
+-- table so past orders and coupons still resolve.
+ALTER TABLE goods
+    ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT TRUE;
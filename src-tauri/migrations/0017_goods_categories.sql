@@ -0,0 +1,48 @@
+CREATE TABLE IF NOT EXISTS goods_category (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(20) NOT NULL UNIQUE,
+    display_order INT NOT NULL DEFAULT 0, -- Lower comes first on the menu
+    icon VARCHAR(255), -- Icon name or image URL
+    is_active BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+-- The categories the admin page has always offered, then any other
+-- goods_type already in use.
+INSERT IGNORE INTO goods_category (name, display_order) VALUES
+('咖啡类', 1),
+('非咖啡饮品', 2),
+('烘焙食品', 3),
+('轻食简餐', 4),
+('咖啡豆与周边', 5);
+
+INSERT IGNORE INTO goods_category (name, display_order)
+SELECT DISTINCT TRIM(goods_type), 100 FROM goods
+WHERE goods_type IS NOT NULL AND TRIM(goods_type) <> '';
+
+ALTER TABLE goods
+    ADD COLUMN category_id INT AFTER goods_type,
+    ADD CONSTRAINT fk_goods_category FOREIGN KEY (category_id) REFERENCES goods_category (id);
+
+UPDATE goods g
+JOIN goods_category c ON c.name = TRIM(g.goods_type)
+SET g.category_id = c.id;
+
+-- Tax rates move to the category as well. A rate for a goods_type no goods
+-- ever used has nothing to apply to and is dropped.
+ALTER TABLE tax_rates
+    ADD COLUMN category_id INT AFTER id;
+
+UPDATE tax_rates r
+JOIN goods_category c ON c.name = TRIM(r.goods_type)
+SET r.category_id = c.id;
+
+DELETE FROM tax_rates WHERE category_id IS NULL;
+
+ALTER TABLE tax_rates
+    DROP COLUMN goods_type,
+    MODIFY COLUMN category_id INT NOT NULL,
+    ADD UNIQUE KEY uq_tax_rates_category (category_id),
+    ADD CONSTRAINT fk_tax_rates_category FOREIGN KEY (category_id) REFERENCES goods_category (id) ON DELETE CASCADE;
+
+ALTER TABLE goods
+    DROP COLUMN goods_type;
@@ -0,0 +1,21 @@
+-- Sizes, temperatures and the like. A variant sells at the parent's price
+-- plus price_delta. With stock NULL it draws on the parent's stock;
+-- otherwise it keeps its own count.
+CREATE TABLE IF NOT EXISTS goods_variant (
+    id INT AUTO_INCREMENT PRIMARY KEY,
+    goods_id INT NOT NULL,
+    variant_name VARCHAR(20) NOT NULL,
+    price_delta DECIMAL(10, 2) NOT NULL DEFAULT 0,
+    sku VARCHAR(64) NULL,
+    stock INT NULL,
+    display_order INT NOT NULL DEFAULT 0,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    FOREIGN KEY (goods_id) REFERENCES goods (id) ON DELETE CASCADE,
+    UNIQUE KEY uq_goods_variant_name (goods_id, variant_name),
+    UNIQUE KEY uq_goods_variant_sku (sku)
+);
+
+-- NULL for goods sold without a variant. Analytics roll lines up by goods_id.
+ALTER TABLE order_items
+    ADD COLUMN variant_id INT NULL AFTER goods_id,
+    ADD CONSTRAINT fk_order_items_variant FOREIGN KEY (variant_id) REFERENCES goods_variant (id);
@@ -1,23 +1,35 @@
+use crate::error::{CafeHubError, Entity};
+use crate::gift_cards::{self, GiftCardTxnType};
+use crate::ledger::{self, LedgerEntry, TransactionType};
+use crate::loyalty::{self, PointsTxnType};
+use crate::migrations;
 use crate::models::*;
+use crate::permissions::{self, Permission, CUSTOMER_ROLE_ID, MANAGER_ROLE_ID};
+use crate::session::{SessionStore, SESSION_IDLE_TIMEOUT};
+use crate::tax;
+use crate::tiers;
 use bcrypt::{hash, verify, DEFAULT_COST};
-use chrono::{Datelike, Local, NaiveDate};
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime};
 use mysql::{params, prelude::Queryable, Error as MySQLError, Pool};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use std::collections::HashMap;
 use tauri::State;
 
 #[tauri::command]
 pub fn login(
     username: String,
     password: String,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<Account, String> {
+) -> Result<LoginResponse, CafeHubError> {
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
-    let result: Result<Option<(i64, String, String, Option<String>, Option<i8>, Option<NaiveDate>, Option<Decimal>, i8)>, mysql::Error> =
+    let result: Result<Option<(i64, String, String, Option<String>, Option<i8>, Option<NaiveDate>, Option<Decimal>, i32, Option<i32>, Option<i32>)>, mysql::Error> =
         conn.exec_first(
-            "SELECT id, username, password, phone, gender, join_time, balance, user_type FROM account WHERE username = :username",
+            "SELECT id, username, password, phone, gender, join_time, balance, points, tier_id, role_id FROM account WHERE username = :username",
             params! {"username" => &username},
         );
 
@@ -30,14 +42,32 @@ pub fn login(
             gender,
             join_time,
             balance,
-            user_type,
+            points,
+            tier_id,
+            role_id,
         ))) => {
             let valid_password = verify(&password, &stored_hashed_password).map_err(|e| {
                 eprintln!("Password verification error for user {}: {}", username, e);
-                "Password verification process failed".to_string()
+                CafeHubError::internal("Password verification process failed")
             })?;
 
             if valid_password {
+                let role = match role_id {
+                    Some(role_id) => permissions::load_account_role(&mut conn, role_id)
+                        .map_err(|e| CafeHubError::database("Database query failed", e))?,
+                    None => None,
+                }
+                .ok_or_else(|| {
+                    eprintln!("Login refused for user {}: no role assigned", username);
+                    CafeHubError::internal("Account has no role assigned")
+                })?;
+                let role_permissions = permissions::load_role_permissions(&mut conn, role.id)
+                    .map_err(|e| CafeHubError::database("Database query failed", e))?;
+                let tier = match tier_id {
+                    Some(tier_id) => tiers::load_account_tier(&mut conn, tier_id)
+                        .map_err(|e| CafeHubError::database("Database query failed", e))?,
+                    None => None,
+                };
                 let account = Account {
                     id,
                     username: uname,
@@ -45,57 +75,73 @@ pub fn login(
                     gender,
                     join_time,
                     balance,
-                    user_type,
+                    points,
+                    tier,
+                    role,
                 };
-                Ok(account)
+                let token = sessions.create(account.id, account.role.id, role_permissions);
+                Ok(LoginResponse {
+                    token,
+                    expires_in_secs: SESSION_IDLE_TIMEOUT.as_secs(),
+                    account,
+                })
             } else {
                 println!("Login failed for user {}: Invalid password", username);
-                Err("Invalid username or password".to_string())
+                Err(CafeHubError::InvalidCredentials)
             }
         }
         Ok(None) => {
             println!("Login failed for user {}: User not found", username);
-            Err("Invalid username or password".to_string())
+            Err(CafeHubError::InvalidCredentials)
         }
         Err(e) => {
             eprintln!("Database query failed for user {}: {}", username, e);
-            Err(format!("Database query failed: {}", e))
+            Err(CafeHubError::database("Database query failed", e))
         }
     }
 }
 
 #[tauri::command]
-pub fn register_user(data: RegistrationData, mysql_pool: State<Pool>) -> Result<i32, String> {
-    if data.username.is_empty() || data.password.is_empty() {
-        return Err("Username and password cannot be empty".to_string());
+pub fn logout(token: String, sessions: State<SessionStore>) -> Result<(), CafeHubError> {
+    sessions.revoke(&token);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn register_user(data: RegistrationData, mysql_pool: State<Pool>) -> Result<(), CafeHubError> {
+    if data.username.is_empty() {
+        return Err(CafeHubError::EmptyField { field: "username" });
+    }
+    if data.password.is_empty() {
+        return Err(CafeHubError::EmptyField { field: "password" });
     }
 
     if let Some(gender_val) = data.gender {
         if gender_val != 0 && gender_val != 1 {
-            return Ok(4);
+            return Err(CafeHubError::InvalidGender);
         }
     }
 
     if let Some(ref phone_str) = data.phone {
         if !phone_str.chars().all(|c| c.is_ascii_digit()) || phone_str.len() != 11 {
-            return Ok(3);
+            return Err(CafeHubError::InvalidPhone);
         }
     }
 
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
     let current_date = Local::now().date_naive();
     let user_type: i8 = 1;
     let default_balance: Decimal = Decimal::new(0, 2);
 
     let hashed_password = hash(&data.password, DEFAULT_COST).map_err(|e| {
         eprintln!("Failed to hash password for user {}: {}", data.username, e);
-        "Password hashing failed".to_string()
+        CafeHubError::internal("Password hashing failed")
     })?;
 
     let result = conn.exec_drop(
-        "INSERT INTO account (username, password, phone, gender, join_time, balance, user_type) VALUES (:username, :password, :phone, :gender, :join_time, :balance, :user_type)",
+        "INSERT INTO account (username, password, phone, gender, join_time, balance, user_type, role_id) VALUES (:username, :password, :phone, :gender, :join_time, :balance, :user_type, :role_id)",
         params! {
             "username" => &data.username,
             "password" => &hashed_password,
@@ -104,39 +150,54 @@ pub fn register_user(data: RegistrationData, mysql_pool: State<Pool>) -> Result<
             "join_time" => current_date,
             "balance" => default_balance,
             "user_type" => user_type,
+            "role_id" => CUSTOMER_ROLE_ID,
         }
     );
 
     match result {
-        Ok(_) => Ok(1),
+        Ok(_) => Ok(()),
         Err(e) => {
             eprintln!("Database insert failed for user {}: {}", data.username, e);
             if let MySQLError::MySqlError(ref mysql_err) = e {
                 if mysql_err.code == 1062 {
-                    return Ok(2);
+                    return Err(CafeHubError::UsernameTaken {
+                        username: data.username,
+                    });
                 }
             }
-            Err(format!("Database error during registration: {}", e))
+            Err(CafeHubError::database(
+                "Database error during registration",
+                e,
+            ))
         }
     }
 }
 
 #[tauri::command]
 pub fn update_user_password(
-    user_id: i64,
+    token: String,
     data: UpdatePasswordData,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<String, String> {
+) -> Result<String, CafeHubError> {
+    let user_id = sessions
+        .authorize(&token, Permission::ManageOwnAccount)?
+        .user_id;
+
     if data.new_password.is_empty() {
-        return Err("New password cannot be empty".to_string());
+        return Err(CafeHubError::EmptyField {
+            field: "new_password",
+        });
     }
     if data.current_password.is_empty() {
-        return Err("Current password cannot be empty".to_string());
+        return Err(CafeHubError::EmptyField {
+            field: "current_password",
+        });
     }
 
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let stored_hashed_password_result: Result<Option<String>, mysql::Error> = conn.exec_first(
         "SELECT password FROM account WHERE id = :user_id AND user_type = 1",
@@ -146,17 +207,14 @@ pub fn update_user_password(
     let stored_hashed_password = match stored_hashed_password_result {
         Ok(Some(hash)) => hash,
         Ok(None) => {
-            return Err(format!(
-                "User with ID {} not found or is not a customer.",
-                user_id
-            ));
+            return Err(CafeHubError::not_found(Entity::Customer, user_id));
         }
         Err(e) => {
             eprintln!(
                 "Database query failed for current password (ID {}): {}",
                 user_id, e
             );
-            return Err(format!("Database query failed: {}", e));
+            return Err(CafeHubError::database("Database query failed", e));
         }
     };
 
@@ -166,16 +224,16 @@ pub fn update_user_password(
                 "Current password verification error for user ID {}: {}",
                 user_id, e
             );
-            "Password verification process failed".to_string()
+            CafeHubError::internal("Password verification process failed")
         })?;
 
     if !valid_current_password {
-        return Err("Incorrect current password".to_string());
+        return Err(CafeHubError::IncorrectPassword);
     }
 
     let new_hashed_password = hash(&data.new_password, DEFAULT_COST).map_err(|e| {
         eprintln!("Failed to hash new password for user ID {}: {}", user_id, e);
-        "Password hashing failed".to_string()
+        CafeHubError::internal("Password hashing failed")
     })?;
 
     let update_result = conn.exec_drop(
@@ -189,9 +247,10 @@ pub fn update_user_password(
     match update_result {
         Ok(_) => {
             if conn.affected_rows() > 0 {
+                sessions.revoke_other_sessions(user_id, &token);
                 Ok("Password updated successfully.".to_string())
             } else {
-                Err("Failed to update password, user not found or no change made.".to_string())
+                Err(CafeHubError::not_found(Entity::Customer, user_id))
             }
         }
         Err(e) => {
@@ -199,16 +258,22 @@ pub fn update_user_password(
                 "Database password update failed for user ID {}: {}",
                 user_id, e
             );
-            Err(format!("Database password update failed: {}", e))
+            Err(CafeHubError::database("Database password update failed", e))
         }
     }
 }
 
 #[tauri::command]
-pub fn get_total_users(mysql_pool: State<Pool>) -> Result<i64, String> {
+pub fn get_total_users(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<i64, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewAnalytics)?;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
     let count: Result<Option<i64>, mysql::Error> =
         conn.query_first("SELECT COUNT(*) FROM account WHERE user_type = 1");
 
@@ -217,22 +282,28 @@ pub fn get_total_users(mysql_pool: State<Pool>) -> Result<i64, String> {
         Ok(None) => Ok(0),
         Err(e) => {
             eprintln!("Database query failed for total users: {}", e);
-            Err(format!("Database query failed: {}", e))
+            Err(CafeHubError::database("Database query failed", e))
         }
     }
 }
 
 #[tauri::command]
-pub fn get_new_users_this_month(mysql_pool: State<Pool>) -> Result<i64, String> {
+pub fn get_new_users_this_month(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<i64, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewAnalytics)?;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
     let now = Local::now();
     let current_year = now.year();
     let current_month_num = now.month();
 
     let first_day_current_month = NaiveDate::from_ymd_opt(current_year, current_month_num, 1)
-        .ok_or_else(|| "Failed to construct first day of current month".to_string())?;
+        .ok_or_else(|| CafeHubError::internal("Failed to construct first day of current month"))?;
 
     let count: Result<Option<i64>, mysql::Error> = conn.exec_first(
         "SELECT COUNT(*) FROM account WHERE user_type = 1 AND join_time >= :start_date",
@@ -246,61 +317,98 @@ pub fn get_new_users_this_month(mysql_pool: State<Pool>) -> Result<i64, String>
         Ok(None) => Ok(0),
         Err(e) => {
             eprintln!("Database query failed for new users this month: {}", e);
-            Err(format!("Database query failed: {}", e))
+            Err(CafeHubError::database("Database query failed", e))
         }
     }
 }
 
 #[tauri::command]
 pub fn get_monthly_consumption_summary(
+    token: String,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<Vec<MonthlyConsumptionSummary>, String> {
+) -> Result<Vec<MonthlyConsumptionSummary>, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewAnalytics)?;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
-    let query = "SELECT month, SUM(amount) as total_amount FROM consumption GROUP BY month ORDER BY month ASC";
+    let query = "
+        SELECT DATE_FORMAT(created_at, '%Y-%m') AS month, SUM(total_amount - refunded_amount) as total_amount,
+               SUM(CASE WHEN total_amount > 0 THEN tax_amount * (total_amount - refunded_amount) / total_amount ELSE 0 END) as tax_amount
+        FROM orders
+        GROUP BY month
+        ORDER BY month ASC";
 
     let results: Vec<MonthlyConsumptionSummary> = conn
-        .query_map(query, |(month, total_amount)| MonthlyConsumptionSummary {
-            month,
-            total_amount,
-        })
+        .query_map(
+            query,
+            |(month, total_amount, tax_amount): (String, Decimal, Decimal)| {
+                MonthlyConsumptionSummary {
+                    month,
+                    total_amount,
+                    tax_amount: tax_amount.round_dp(2),
+                }
+            },
+        )
         .map_err(|e| {
-            format!(
-                "Database query failed for monthly consumption summary: {}",
-                e
-            )
+            CafeHubError::database("Database query failed for monthly consumption summary", e)
         })?;
 
     Ok(results)
 }
 
+/// Rolls variants up to their goods unless `by_variant` asks for one row
+/// per variant.
 #[tauri::command]
 pub fn get_goods_consumption_share_current_month(
+    token: String,
+    by_variant: Option<bool>,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<Vec<GoodsConsumptionShare>, String> {
+) -> Result<Vec<GoodsConsumptionShare>, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewAnalytics)?;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let now = Local::now();
     let current_month_str = now.format("%Y-%m").to_string();
-
-    let query = "
-        SELECT g.goods_name, SUM(c.amount) as consumed_amount
-        FROM consumption c
-        JOIN goods g ON c.goods_id = g.id
-        WHERE c.month = :current_month
+    let month_start = NaiveDate::from_ymd_opt(now.year(), now.month(), 1)
+        .ok_or_else(|| CafeHubError::internal("Failed to construct first day of current month"))?;
+
+    let query = if by_variant.unwrap_or(false) {
+        "
+        SELECT g.goods_name, v.variant_name, SUM(oi.line_total - oi.refunded_amount) as consumed_amount
+        FROM order_items oi
+        JOIN orders o ON oi.order_id = o.id
+        JOIN goods g ON oi.goods_id = g.id
+        LEFT JOIN goods_variant v ON oi.variant_id = v.id
+        WHERE o.created_at >= :month_start
+        GROUP BY g.goods_name, oi.variant_id, v.variant_name
+        ORDER BY consumed_amount DESC"
+    } else {
+        "
+        SELECT g.goods_name, NULL, SUM(oi.line_total - oi.refunded_amount) as consumed_amount
+        FROM order_items oi
+        JOIN orders o ON oi.order_id = o.id
+        JOIN goods g ON oi.goods_id = g.id
+        WHERE o.created_at >= :month_start
         GROUP BY g.goods_name
-        ORDER BY consumed_amount DESC";
+        ORDER BY consumed_amount DESC"
+    };
 
     match conn.exec_map(
         query,
-        params! { "current_month" => &current_month_str },
-        |(goods_name, amount_val): (String, Decimal)| GoodsConsumptionShare {
-            goods_name,
-            amount: amount_val,
+        params! { "month_start" => month_start },
+        |(goods_name, variant_name, amount_val): (String, Option<String>, Decimal)| {
+            GoodsConsumptionShare {
+                goods_name,
+                variant_name,
+                amount: amount_val,
+            }
         },
     ) {
         Ok(results) => Ok(results),
@@ -309,62 +417,87 @@ pub fn get_goods_consumption_share_current_month(
                 "[RUST ERROR] Database query failed for current month goods consumption share (month: {}): {}",
                 current_month_str, e
             );
-            Err(format!(
-                "Database query failed for current month goods consumption share: {}",
-                e
+            Err(CafeHubError::database(
+                "Database query failed for current month goods consumption share",
+                e,
             ))
         }
     }
 }
 
 #[tauri::command]
-pub fn get_user_details(user_id: i64, mysql_pool: State<Pool>) -> Result<Account, String> {
+pub fn get_user_details(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Account, CafeHubError> {
+    let user_id = sessions
+        .authorize(&token, Permission::ManageOwnAccount)?
+        .user_id;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    refresh_points(&mut conn, user_id)?;
 
-    let result: Result<Option<(i64, String, Option<String>, Option<i8>, Option<NaiveDate>, Option<Decimal>, i8)>, mysql::Error> =
+    let result: Result<Option<(i64, String, Option<String>, Option<i8>, Option<NaiveDate>, Option<Decimal>, i32, Option<i32>, i32)>, mysql::Error> =
         conn.exec_first(
-            "SELECT id, username, phone, gender, join_time, balance, user_type FROM account WHERE id = :user_id AND user_type = 1",
+            "SELECT id, username, phone, gender, join_time, balance, points, tier_id, role_id FROM account WHERE id = :user_id AND user_type = 1",
             params! { "user_id" => user_id },
         );
 
     match result {
-        Ok(Some((id, username, phone, gender, join_time, balance, user_type))) => Ok(Account {
-            id,
-            username,
-            phone,
-            gender,
-            join_time,
-            balance,
-            user_type,
-        }),
-        Ok(None) => Err(format!(
-            "User with ID {} not found or is not a customer.",
-            user_id
-        )),
+        Ok(Some((id, username, phone, gender, join_time, balance, points, tier_id, role_id))) => {
+            let role = permissions::load_account_role(&mut conn, role_id)
+                .map_err(|e| CafeHubError::database("Database query failed", e))?
+                .ok_or_else(|| CafeHubError::not_found(Entity::Role, role_id))?;
+            let tier = match tier_id {
+                Some(tier_id) => tiers::load_account_tier(&mut conn, tier_id)
+                    .map_err(|e| CafeHubError::database("Database query failed", e))?,
+                None => None,
+            };
+            Ok(Account {
+                id,
+                username,
+                phone,
+                gender,
+                join_time,
+                balance,
+                points,
+                tier,
+                role,
+            })
+        }
+        Ok(None) => Err(CafeHubError::not_found(Entity::Customer, user_id)),
         Err(e) => {
             eprintln!(
                 "Database query failed for user details (ID {}): {}",
                 user_id, e
             );
-            Err(format!("Database query failed: {}", e))
+            Err(CafeHubError::database("Database query failed", e))
         }
     }
 }
 
 #[tauri::command]
 pub fn get_user_monthly_consumption(
-    user_id: i64,
+    token: String,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<Vec<MonthlyConsumptionSummary>, String> {
+) -> Result<Vec<MonthlyConsumptionSummary>, CafeHubError> {
+    let user_id = sessions
+        .authorize(&token, Permission::ManageOwnAccount)?
+        .user_id;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let query = "
-        SELECT month, SUM(amount) as total_amount
-        FROM consumption
+        SELECT DATE_FORMAT(created_at, '%Y-%m') AS month, SUM(total_amount - refunded_amount) as total_amount,
+               SUM(CASE WHEN total_amount > 0 THEN tax_amount * (total_amount - refunded_amount) / total_amount ELSE 0 END) as tax_amount
+        FROM orders
         WHERE user_id = :user_id
         GROUP BY month
         ORDER BY month ASC";
@@ -373,32 +506,42 @@ pub fn get_user_monthly_consumption(
         .exec_map(
             query,
             params! { "user_id" => user_id },
-            |(month, total_amount)| MonthlyConsumptionSummary {
-                month,
-                total_amount,
+            |(month, total_amount, tax_amount): (String, Decimal, Decimal)| {
+                MonthlyConsumptionSummary {
+                    month,
+                    total_amount,
+                    tax_amount: tax_amount.round_dp(2),
+                }
             },
         )
-        .map_err(|e| format!("Database query failed for user monthly consumption: {}", e))?;
+        .map_err(|e| {
+            CafeHubError::database("Database query failed for user monthly consumption", e)
+        })?;
 
     Ok(results)
 }
 
 #[tauri::command]
 pub fn update_user_details(
-    user_id: i64,
+    token: String,
     data: UpdateUserData,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<String, String> {
+) -> Result<String, CafeHubError> {
+    let user_id = sessions
+        .authorize(&token, Permission::ManageOwnAccount)?
+        .user_id;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let mut set_clauses: Vec<String> = Vec::new();
     let mut query_params_vec: Vec<(String, mysql::Value)> = Vec::new();
 
     if let Some(uname_val) = &data.username {
         if uname_val.is_empty() {
-            return Err("Username cannot be empty.".to_string());
+            return Err(CafeHubError::EmptyField { field: "username" });
         }
 
         let current_username_check: Option<String> = conn
@@ -406,7 +549,7 @@ pub fn update_user_details(
                 "SELECT username FROM account WHERE id = :user_id",
                 params! { "user_id" => user_id },
             )
-            .map_err(|e| format!("DB error checking current username: {}", e))?;
+            .map_err(|e| CafeHubError::database("DB error checking current username", e))?;
 
         let is_changing_username = match current_username_check {
             Some(ref current_uname) => current_uname != uname_val,
@@ -417,10 +560,12 @@ pub fn update_user_details(
             let existing_user: Option<i64> = conn.exec_first(
                 "SELECT id FROM account WHERE username = :username AND id != :user_id_to_exclude",
                 params! { "username" => uname_val, "user_id_to_exclude" => user_id }
-            ).map_err(|e| format!("DB error checking username uniqueness: {}", e))?;
+            ).map_err(|e| CafeHubError::database("DB error checking username uniqueness", e))?;
 
             if existing_user.is_some() {
-                return Err(format!("Username '{}' is already taken.", uname_val));
+                return Err(CafeHubError::UsernameTaken {
+                    username: uname_val.clone(),
+                });
             }
             set_clauses.push("username = :username".to_string());
             query_params_vec.push(("username".to_string(), uname_val.clone().into()));
@@ -432,7 +577,7 @@ pub fn update_user_details(
             set_clauses.push("phone = NULL".to_string());
         } else {
             if !phone_val.chars().all(|c| c.is_ascii_digit()) || phone_val.len() != 11 {
-                return Err("Invalid phone number format. Must be 11 digits.".to_string());
+                return Err(CafeHubError::InvalidPhone);
             }
             set_clauses.push("phone = :phone".to_string());
             query_params_vec.push(("phone".to_string(), phone_val.clone().into()));
@@ -444,7 +589,7 @@ pub fn update_user_details(
             set_clauses.push("gender = :gender".to_string());
             query_params_vec.push(("gender".to_string(), gender_val.into()));
         } else {
-            return Err("Invalid gender value. Must be 0 (Male) or 1 (Female).".to_string());
+            return Err(CafeHubError::InvalidGender);
         }
     }
 
@@ -472,114 +617,423 @@ pub fn update_user_details(
             eprintln!("Database update failed for user ID {}: {}", user_id, e);
             if let MySQLError::MySqlError(ref mysql_err) = e {
                 if mysql_err.code == 1062 {
-                    return Err("Update failed: Username or Phone number might already be in use by another account.".to_string());
+                    return Err(CafeHubError::UsernameTaken {
+                        username: data.username.clone().unwrap_or_default(),
+                    });
                 }
             }
-            Err(format!("Database update failed: {}", e))
+            Err(CafeHubError::database("Database update failed", e))
         }
     }
 }
 
 #[tauri::command]
-pub fn get_all_goods(mysql_pool: State<Pool>) -> Result<Vec<Goods>, String> {
+pub fn get_all_goods(
+    token: String,
+    include_archived: Option<bool>,
+    group_by_category: Option<bool>,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<GoodsListing, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewGoods)?;
+    // Archived goods are only listed for staff who can restore them.
+    let include_archived = include_archived.unwrap_or(false);
+    if include_archived {
+        sessions.authorize(&token, Permission::ManageGoods)?;
+    }
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
-    let query = "SELECT id, goods_name, goods_type, price, stock FROM goods";
+    // Goods in an inactive category are off the menu along with it.
+    let query = format!(
+        "SELECT g.id, g.goods_name, c.name, g.category_id, g.price, g.stock, g.points_price, g.is_active
+         FROM goods g
+         LEFT JOIN goods_category c ON g.category_id = c.id
+         {}
+         ORDER BY g.id ASC",
+        if include_archived {
+            ""
+        } else {
+            "WHERE g.is_active = 1 AND (c.is_active IS NULL OR c.is_active = 1)"
+        }
+    );
 
     let results: Vec<Goods> = conn
-        .query_map(query, |(id, goods_name, goods_type, price, stock)| Goods {
-            id,
-            goods_name,
-            goods_type,
-            price,
-            stock,
+        .query_map(
+            query,
+            |(id, goods_name, goods_type, category_id, price, stock, points_price, is_active)| {
+                Goods {
+                    id,
+                    goods_name,
+                    goods_type,
+                    category_id,
+                    price,
+                    stock,
+                    points_price,
+                    is_active,
+                }
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for all goods", e))?;
+
+    if !group_by_category.unwrap_or(false) {
+        return Ok(GoodsListing::Flat(results));
+    }
+
+    // Categories in menu order, then anything uncategorized. Empty
+    // categories are left out.
+    let categories = load_goods_categories(&mut conn)?;
+    let mut groups: Vec<GoodsCategoryGroup> = categories
+        .into_iter()
+        .map(|category| GoodsCategoryGroup {
+            category: Some(category),
+            goods: Vec::new(),
         })
-        .map(|items| items)
-        .map_err(|e| format!("Database query failed for all goods: {}", e))?;
+        .collect();
+    let mut uncategorized: Vec<Goods> = Vec::new();
+    for goods in results {
+        let group = groups
+            .iter_mut()
+            .find(|group| group.category.as_ref().map(|category| category.id) == goods.category_id);
+        match group {
+            Some(group) => group.goods.push(goods),
+            None => uncategorized.push(goods),
+        }
+    }
+    groups.retain(|group| !group.goods.is_empty());
+    if !uncategorized.is_empty() {
+        groups.push(GoodsCategoryGroup {
+            category: None,
+            goods: uncategorized,
+        });
+    }
 
-    Ok(results)
+    Ok(GoodsListing::Grouped(groups))
+}
+
+/// Escapes `%`, `_` and `\` so user input matches literally in LIKE.
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Catalogue search with paging. Filters combine; `total` counts every
+/// match, not just the returned page.
+#[tauri::command]
+pub fn search_goods(
+    token: String,
+    data: GoodsQuery,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<GoodsPage, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewGoods)?;
+    let include_archived = data.include_archived.unwrap_or(false);
+    if include_archived {
+        sessions.authorize(&token, Permission::ManageGoods)?;
+    }
+
+    for (field, price) in [("min_price", data.min_price), ("max_price", data.max_price)] {
+        if price.is_some_and(|price| price < Decimal::ZERO) {
+            return Err(CafeHubError::NegativeValue { field });
+        }
+    }
+    if let (Some(min_price), Some(max_price)) = (data.min_price, data.max_price) {
+        if min_price > max_price {
+            return Err(CafeHubError::InvalidPriceRange {
+                min_price,
+                max_price,
+            });
+        }
+    }
+
+    let (page, page_size) = page_bounds(data.page, data.page_size);
+
+    let mut conditions: Vec<String> = Vec::new();
+    let mut query_params: Vec<(String, mysql::Value)> = Vec::new();
+    if !include_archived {
+        conditions.push("g.is_active = 1 AND (c.is_active IS NULL OR c.is_active = 1)".to_string());
+    }
+    if let Some(search) = data
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|search| !search.is_empty())
+    {
+        conditions.push("g.goods_name LIKE :search".to_string());
+        query_params.push(("search".to_string(), like_pattern(search).into()));
+    }
+    if let Some(category_id) = data.category_id {
+        conditions.push("g.category_id = :category_id".to_string());
+        query_params.push(("category_id".to_string(), category_id.into()));
+    }
+    if let Some(min_price) = data.min_price {
+        conditions.push("g.price >= :min_price".to_string());
+        query_params.push(("min_price".to_string(), min_price.into()));
+    }
+    if let Some(max_price) = data.max_price {
+        conditions.push("g.price <= :max_price".to_string());
+        query_params.push(("max_price".to_string(), max_price.into()));
+    }
+    if data.in_stock_only.unwrap_or(false) {
+        conditions.push("g.stock > 0".to_string());
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let sort_column = match data.sort_by.unwrap_or(GoodsSortKey::Id) {
+        GoodsSortKey::Id => "g.id",
+        GoodsSortKey::Name => "g.goods_name",
+        GoodsSortKey::Price => "g.price",
+        GoodsSortKey::Stock => "g.stock",
+        GoodsSortKey::Category => "c.display_order",
+    };
+    let direction = if data.descending.unwrap_or(false) {
+        "DESC"
+    } else {
+        "ASC"
+    };
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let count_query = format!(
+        "SELECT COUNT(*) FROM goods g LEFT JOIN goods_category c ON g.category_id = c.id {}",
+        where_clause
+    );
+    let total: Option<i64> = if query_params.is_empty() {
+        conn.query_first(count_query)
+    } else {
+        conn.exec_first(count_query, mysql::Params::from(query_params.clone()))
+    }
+    .map_err(|e| CafeHubError::database("Database query failed for goods count", e))?;
+
+    query_params.push(("limit".to_string(), page_size.into()));
+    query_params.push((
+        "offset".to_string(),
+        (u64::from(page - 1) * u64::from(page_size)).into(),
+    ));
+
+    let query = format!(
+        "SELECT g.id, g.goods_name, c.name, g.category_id, g.price, g.stock, g.points_price, g.is_active
+         FROM goods g
+         LEFT JOIN goods_category c ON g.category_id = c.id
+         {}
+         ORDER BY {} {}, g.id {}
+         LIMIT :limit OFFSET :offset",
+        where_clause, sort_column, direction, direction
+    );
+
+    let goods: Vec<Goods> = conn
+        .exec_map(
+            query,
+            mysql::Params::from(query_params),
+            |(id, goods_name, goods_type, category_id, price, stock, points_price, is_active)| {
+                Goods {
+                    id,
+                    goods_name,
+                    goods_type,
+                    category_id,
+                    price,
+                    stock,
+                    points_price,
+                    is_active,
+                }
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for goods search", e))?;
+
+    Ok(GoodsPage {
+        goods,
+        total: total.unwrap_or(0),
+        page,
+        page_size,
+    })
 }
 
 #[tauri::command]
-pub fn add_goods(data: AddGoodsData, mysql_pool: State<Pool>) -> Result<String, String> {
-    if data.goods_name.is_empty() {
-        return Err("Goods name cannot be empty".to_string());
+pub fn add_goods(
+    token: String,
+    data: AddGoodsData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<String, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+
+    let goods_name = data.goods_name.trim();
+    validate_goods_name(goods_name)?;
+    if data.price < Decimal::ZERO {
+        return Err(CafeHubError::NegativeValue { field: "price" });
     }
-    if data.price <= Decimal::ZERO {
-        return Err("Price must be positive".to_string());
+    if data.points_price.is_some_and(|points| points <= 0) {
+        return Err(CafeHubError::NonPositiveAmount {
+            field: "points_price",
+        });
     }
 
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    if let Some(category_id) = data.category_id {
+        ensure_category_exists(&mut conn, category_id)?;
+    }
+    if goods_name_taken(&mut conn, goods_name, data.category_id, None)? {
+        return Err(CafeHubError::NameTaken {
+            entity: Entity::Goods,
+            name: goods_name.to_string(),
+        });
+    }
 
     let stock_value = data.stock.unwrap_or(0);
 
     let result = conn.exec_drop(
-        "INSERT INTO goods (goods_name, goods_type, price, stock) VALUES (:goods_name, :goods_type, :price, :stock)",
+        "INSERT INTO goods (goods_name, category_id, price, stock, points_price) VALUES (:goods_name, :category_id, :price, :stock, :points_price)",
         params! {
-            "goods_name" => &data.goods_name,
-            "goods_type" => &data.goods_type,
+            "goods_name" => goods_name,
+            "category_id" => data.category_id,
             "price" => data.price,
             "stock" => stock_value,
+            "points_price" => data.points_price,
         }
     );
 
     match result {
-        Ok(_) => Ok(format!("Goods '{}' added successfully.", data.goods_name)),
+        Ok(_) => Ok(format!("Goods '{}' added successfully.", goods_name)),
         Err(e) => {
-            eprintln!(
-                "Database insert failed for goods {}: {}",
-                data.goods_name, e
-            );
+            eprintln!("Database insert failed for goods {}: {}", goods_name, e);
             if let MySQLError::MySqlError(ref mysql_err) = e {
                 if mysql_err.code == 1062 {
-                    return Err(format!(
-                        "Goods with name '{}' already exists.",
-                        data.goods_name
-                    ));
+                    return Err(CafeHubError::NameTaken {
+                        entity: Entity::Goods,
+                        name: goods_name.to_string(),
+                    });
                 }
             }
-            Err(format!("Database error while adding goods: {}", e))
+            Err(CafeHubError::database(
+                "Database error while adding goods",
+                e,
+            ))
         }
     }
 }
 
 #[tauri::command]
 pub fn update_goods_info(
+    token: String,
     goods_id: i32,
     data: UpdateGoodsData,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<String, String> {
-    let mut conn = mysql_pool
-        .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+) -> Result<String, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
 
     let mut set_clauses: Vec<String> = Vec::new();
     let mut query_params: Vec<(String, mysql::Value)> = Vec::new();
 
+    let goods_name = data.goods_name.as_deref().map(str::trim);
+    if let Some(name) = goods_name {
+        validate_goods_name(name)?;
+        set_clauses.push("goods_name = :goods_name".to_string());
+        query_params.push(("goods_name".to_string(), name.into()));
+    }
+
+    if let Some(category_id) = data.category_id {
+        set_clauses.push("category_id = :category_id".to_string());
+        query_params.push(("category_id".to_string(), category_id.into()));
+    }
+
     if let Some(stock_val) = data.stock {
         if stock_val < 0 {
-            return Err("Stock cannot be negative".to_string());
+            return Err(CafeHubError::NegativeValue { field: "stock" });
         }
         set_clauses.push("stock = :stock".to_string());
         query_params.push(("stock".to_string(), stock_val.into()));
     }
 
     if let Some(price_val) = data.price {
-        if price_val <= Decimal::ZERO {
-            return Err("Price must be positive".to_string());
+        if price_val < Decimal::ZERO {
+            return Err(CafeHubError::NegativeValue { field: "price" });
         }
         set_clauses.push("price = :price".to_string());
         query_params.push(("price".to_string(), price_val.into()));
     }
 
+    if let Some(points_price) = data.points_price {
+        if points_price < 0 {
+            return Err(CafeHubError::NegativeValue {
+                field: "points_price",
+            });
+        }
+        set_clauses.push("points_price = :points_price".to_string());
+        query_params.push((
+            "points_price".to_string(),
+            Some(points_price).filter(|points| *points > 0).into(),
+        ));
+    }
+
     if set_clauses.is_empty() {
         return Ok("No details provided to update.".to_string());
     }
 
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let (current_name, current_category): (String, Option<i32>) = tx
+        .exec_first(
+            "SELECT goods_name, category_id FROM goods WHERE id = :goods_id FOR UPDATE",
+            params! { "goods_id" => goods_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query goods", e))?
+        .ok_or_else(|| CafeHubError::not_found(Entity::Goods, goods_id))?;
+
+    if let Some(category_id) = data.category_id.flatten() {
+        ensure_category_exists(&mut tx, category_id)?;
+    }
+
+    if goods_name.is_some() || data.category_id.is_some() {
+        let name = goods_name.unwrap_or(&current_name);
+        let category_id = data.category_id.unwrap_or(current_category);
+        if goods_name_taken(&mut tx, name, category_id, Some(goods_id))? {
+            return Err(CafeHubError::NameTaken {
+                entity: Entity::Goods,
+                name: name.to_string(),
+            });
+        }
+    }
+
+    // Variants are priced off the parent, so none may end up below zero.
+    if let Some(price_val) = data.price {
+        let cheapest: Option<(i32, Decimal)> = tx
+            .exec_first(
+                "SELECT id, price_delta FROM goods_variant WHERE goods_id = :goods_id
+                 ORDER BY price_delta ASC, id ASC LIMIT 1",
+                params! { "goods_id" => goods_id },
+            )
+            .map_err(|e| CafeHubError::database("Failed to query goods variants", e))?;
+        if let Some((variant_id, price_delta)) = cheapest {
+            if price_val + price_delta < Decimal::ZERO {
+                return Err(CafeHubError::VariantPriceNegative {
+                    goods_id,
+                    variant_id,
+                });
+            }
+        }
+    }
+
     query_params.push(("goods_id".to_string(), goods_id.into()));
 
     let query = format!(
@@ -587,220 +1041,4180 @@ pub fn update_goods_info(
         set_clauses.join(", ")
     );
 
-    match conn.exec_drop(&query, mysql::Params::from(query_params)) {
-        Ok(_) => {
-            if conn.affected_rows() > 0 {
-                Ok(format!(
-                    "Info for goods ID {} updated successfully.",
-                    goods_id
-                ))
-            } else {
-                Err(format!(
-                    "Goods with ID {} not found or no changes made.",
-                    goods_id
-                ))
-            }
-        }
-        Err(e) => {
+    tx.exec_drop(&query, mysql::Params::from(query_params))
+        .map_err(|e| {
             eprintln!(
                 "Database update failed for goods info (ID {}): {}",
                 goods_id, e
             );
-            Err(format!("Database error while updating goods info: {}", e))
-        }
-    }
+            CafeHubError::database("Database error while updating goods info", e)
+        })?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    Ok(format!(
+        "Info for goods ID {} updated successfully.",
+        goods_id
+    ))
 }
 
-#[tauri::command]
-pub fn recharge_balance(
-    data: RechargeBalanceData,
-    mysql_pool: State<Pool>,
-) -> Result<String, String> {
-    if data.amount <= Decimal::ZERO {
-        return Err("Recharge amount must be positive".to_string());
+fn validate_goods_name(name: &str) -> Result<(), CafeHubError> {
+    if name.is_empty() {
+        return Err(CafeHubError::EmptyField {
+            field: "goods_name",
+        });
     }
+    if name.chars().count() > 20 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "goods_name",
+            max_length: 20,
+        });
+    }
+    Ok(())
+}
+
+/// Whether other goods in the category already use `name`.
+fn goods_name_taken<Q: Queryable>(
+    conn: &mut Q,
+    name: &str,
+    category_id: Option<i32>,
+    except_id: Option<i32>,
+) -> Result<bool, CafeHubError> {
+    let count: Option<i64> = conn
+        .exec_first(
+            "SELECT COUNT(*) FROM goods
+             WHERE goods_name = :goods_name AND category_id <=> :category_id
+               AND (:except_id IS NULL OR id <> :except_id)",
+            params! {
+                "goods_name" => name,
+                "category_id" => category_id,
+                "except_id" => except_id,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query goods names", e))?;
+    Ok(count.unwrap_or(0) > 0)
+}
 
+fn set_goods_active(mysql_pool: &Pool, goods_id: i32, is_active: bool) -> Result<(), CafeHubError> {
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
-    let user_exists: Option<i8> = conn
+    let exists: Option<i32> = conn
         .exec_first(
-            "SELECT user_type FROM account WHERE id = :user_id",
-            params! { "user_id" => data.user_id },
+            "SELECT id FROM goods WHERE id = :goods_id",
+            params! { "goods_id" => goods_id },
         )
-        .map_err(|e| format!("Failed to query user: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to query goods", e))?;
+    if exists.is_none() {
+        return Err(CafeHubError::not_found(Entity::Goods, goods_id));
+    }
 
-    match user_exists {
-        Some(1) => {
-            let update_result = conn.exec_drop(
-                "UPDATE account SET balance = balance + :amount WHERE id = :user_id AND user_type = 1",
-                params! {
-                    "amount" => data.amount,
-                    "user_id" => data.user_id,
-                },
-            );
+    conn.exec_drop(
+        "UPDATE goods SET is_active = :is_active WHERE id = :goods_id",
+        params! {
+            "is_active" => is_active,
+            "goods_id" => goods_id,
+        },
+    )
+    .map_err(|e| {
+        eprintln!(
+            "Database update failed for goods status (ID {}): {}",
+            goods_id, e
+        );
+        CafeHubError::database("Database error while updating goods status", e)
+    })
+}
 
-            match update_result {
-                Ok(_) => {
-                    if conn.affected_rows() > 0 {
-                        Ok(format!(
-                            "Successfully recharged {} for user ID {}.",
-                            data.amount, data.user_id
-                        ))
-                    } else {
-                        Err(format!(
-                            "Failed to recharge balance for user ID {}. User not found or no change made.",
-                            data.user_id
-                        ))
-                    }
-                }
-                Err(e) => {
-                    eprintln!(
-                        "Database update failed for balance recharge (user ID {}): {}",
-                        data.user_id, e
-                    );
-                    Err(format!("Database error while recharging balance: {}", e))
-                }
-            }
-        }
-        Some(_) => Err(format!("User with ID {} is not a customer.", data.user_id)),
-        None => Err(format!("User with ID {} not found.", data.user_id)),
-    }
+/// Takes goods off the menu without touching their history.
+#[tauri::command]
+pub fn archive_goods(
+    token: String,
+    goods_id: i32,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+    set_goods_active(&mysql_pool, goods_id, false)
 }
 
 #[tauri::command]
-pub fn purchase_goods(data: PurchaseGoodsData, mysql_pool: State<Pool>) -> Result<i32, String> {
-    if data.items.is_empty() {
-        return Err("No items provided for purchase.".to_string());
-    }
+pub fn restore_goods(
+    token: String,
+    goods_id: i32,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+    set_goods_active(&mysql_pool, goods_id, true)
+}
 
-    for item in &data.items {
-        if item.quantity <= 0 {
-            return Err(format!(
-                "Quantity for goods ID {} must be positive.",
-                item.goods_id
-            ));
-        }
-    }
+/// Deletes goods that were never sold or used by a coupon. Anything with
+/// history has to be archived instead.
+#[tauri::command]
+pub fn delete_goods(
+    token: String,
+    goods_id: i32,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
 
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let mut tx = conn
         .start_transaction(mysql::TxOpts::default())
-        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
 
-    let mut total_purchase_price = Decimal::ZERO;
+    // Holding the row keeps a checkout from adding an order line between the
+    // history check and the delete.
+    tx.exec_first::<i32, _, _>(
+        "SELECT id FROM goods WHERE id = :goods_id FOR UPDATE",
+        params! { "goods_id" => goods_id },
+    )
+    .map_err(|e| CafeHubError::database("Failed to query goods", e))?
+    .ok_or_else(|| CafeHubError::not_found(Entity::Goods, goods_id))?;
 
-    struct ProcessedItemDetail {
-        goods_id: i32,
-        quantity: i32,
-        item_total_price: Decimal,
+    let (order_count, coupon_count): (i64, i64) = tx
+        .exec_first(
+            "SELECT
+                (SELECT COUNT(DISTINCT order_id) FROM order_items WHERE goods_id = :goods_id),
+                (SELECT COUNT(*) FROM coupons WHERE free_goods_id = :goods_id)",
+            params! { "goods_id" => goods_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query goods history", e))?
+        .unwrap_or((0, 0));
+    if order_count > 0 || coupon_count > 0 {
+        return Err(CafeHubError::GoodsInUse {
+            goods_id,
+            order_count,
+            coupon_count,
+        });
     }
-    let mut processed_item_details: Vec<ProcessedItemDetail> = Vec::new();
-
-    for item in &data.items {
-        let goods_info: Option<(Decimal, i32)> = tx
-            .exec_first(
-                "SELECT price, stock FROM goods WHERE id = :goods_id FOR UPDATE",
-                params! { "goods_id" => item.goods_id },
-            )
-            .map_err(|e| format!("Failed to query goods ID {}: {}", item.goods_id, e))?;
 
-        let (price_per_item, current_stock) = match goods_info {
-            Some(info) => info,
-            None => {
-                return Err(format!("Goods with ID {} not found.", item.goods_id));
+    tx.exec_drop(
+        "DELETE FROM goods WHERE id = :goods_id",
+        params! { "goods_id" => goods_id },
+    )
+    .map_err(|e| {
+        // Anything else still pointing at the row
+        if let MySQLError::MySqlError(ref mysql_err) = e {
+            if mysql_err.code == 1451 {
+                return CafeHubError::GoodsInUse {
+                    goods_id,
+                    order_count,
+                    coupon_count,
+                };
             }
-        };
-
-        if current_stock < item.quantity {
-            return Ok(1);
         }
+        eprintln!("Database delete failed for goods ID {}: {}", goods_id, e);
+        CafeHubError::database("Database error while deleting goods", e)
+    })?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    Ok(())
+}
+
+fn validate_goods_variant(data: &GoodsVariantData) -> Result<(), CafeHubError> {
+    let name = data.variant_name.trim();
+    if name.is_empty() {
+        return Err(CafeHubError::EmptyField {
+            field: "variant_name",
+        });
+    }
+    if name.chars().count() > 20 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "variant_name",
+            max_length: 20,
+        });
+    }
+    if data
+        .sku
+        .as_deref()
+        .is_some_and(|sku| sku.trim().chars().count() > 64)
+    {
+        return Err(CafeHubError::FieldTooLong {
+            field: "sku",
+            max_length: 64,
+        });
+    }
+    if data.stock.is_some_and(|stock| stock < 0) {
+        return Err(CafeHubError::NegativeValue { field: "stock" });
+    }
+    Ok(())
+}
+
+/// Checks the parent exists and that its price plus `price_delta` is not
+/// negative. The parent row stays locked so its price cannot change before
+/// the variant is written.
+fn check_variant_price(
+    tx: &mut mysql::Transaction,
+    goods_id: i32,
+    price_delta: Decimal,
+) -> Result<(), CafeHubError> {
+    let price: Decimal = tx
+        .exec_first(
+            "SELECT price FROM goods WHERE id = :goods_id FOR UPDATE",
+            params! { "goods_id" => goods_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query goods", e))?
+        .ok_or_else(|| CafeHubError::not_found(Entity::Goods, goods_id))?;
+    if price + price_delta < Decimal::ZERO {
+        return Err(CafeHubError::NegativeValue {
+            field: "price_delta",
+        });
+    }
+    Ok(())
+}
+
+fn goods_variant_write_error(data: &GoodsVariantData, e: MySQLError) -> CafeHubError {
+    if let MySQLError::MySqlError(ref mysql_err) = e {
+        if mysql_err.code == 1062 {
+            let name = if mysql_err.message.contains("uq_goods_variant_sku") {
+                data.sku.as_deref().unwrap_or_default().trim()
+            } else {
+                data.variant_name.trim()
+            };
+            return CafeHubError::NameTaken {
+                entity: Entity::GoodsVariant,
+                name: name.to_string(),
+            };
+        }
+    }
+    CafeHubError::database("Database error while saving goods variant", e)
+}
+
+/// A goods item's variants in menu order. Inactive ones are only listed for
+/// staff who manage goods.
+#[tauri::command]
+pub fn get_goods_variants(
+    token: String,
+    goods_id: i32,
+    include_inactive: Option<bool>,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<GoodsVariant>, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewGoods)?;
+    let include_inactive = include_inactive.unwrap_or(false);
+    if include_inactive {
+        sessions.authorize(&token, Permission::ManageGoods)?;
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let variants: Vec<GoodsVariant> = conn
+        .exec_map(
+            "SELECT id, goods_id, variant_name, price_delta, sku, stock, display_order, is_active
+             FROM goods_variant
+             WHERE goods_id = :goods_id AND (is_active = 1 OR :include_inactive)
+             ORDER BY display_order ASC, id ASC",
+            params! {
+                "goods_id" => goods_id,
+                "include_inactive" => include_inactive,
+            },
+            |(id, goods_id, variant_name, price_delta, sku, stock, display_order, is_active)| {
+                GoodsVariant {
+                    id,
+                    goods_id,
+                    variant_name,
+                    price_delta,
+                    sku,
+                    stock,
+                    display_order,
+                    is_active,
+                }
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for goods variants", e))?;
+    Ok(variants)
+}
+
+#[tauri::command]
+pub fn create_goods_variant(
+    token: String,
+    goods_id: i32,
+    data: GoodsVariantData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<i32, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+    validate_goods_variant(&data)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    check_variant_price(&mut tx, goods_id, data.price_delta)?;
+
+    tx.exec_drop(
+        "INSERT INTO goods_variant (goods_id, variant_name, price_delta, sku, stock, display_order, is_active)
+         VALUES (:goods_id, :variant_name, :price_delta, :sku, :stock, :display_order, :is_active)",
+        params! {
+            "goods_id" => goods_id,
+            "variant_name" => data.variant_name.trim(),
+            "price_delta" => data.price_delta,
+            "sku" => data.sku.as_deref().map(str::trim).filter(|sku| !sku.is_empty()),
+            "stock" => data.stock,
+            "display_order" => data.display_order,
+            "is_active" => data.is_active,
+        },
+    )
+    .map_err(|e| goods_variant_write_error(&data, e))?;
+
+    let variant_id =
+        tx.last_insert_id()
+            .ok_or_else(|| CafeHubError::internal("Failed to read new variant ID"))? as i32;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    Ok(variant_id)
+}
+
+/// Setting `stock` to None hands the variant back to the parent's stock.
+#[tauri::command]
+pub fn update_goods_variant(
+    token: String,
+    variant_id: i32,
+    data: GoodsVariantData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+    validate_goods_variant(&data)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let goods_id: i32 = conn
+        .exec_first(
+            "SELECT goods_id FROM goods_variant WHERE id = :variant_id",
+            params! { "variant_id" => variant_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query goods variant", e))?
+        .ok_or_else(|| CafeHubError::not_found(Entity::GoodsVariant, variant_id))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    check_variant_price(&mut tx, goods_id, data.price_delta)?;
+
+    tx.exec_drop(
+        "UPDATE goods_variant
+         SET variant_name = :variant_name, price_delta = :price_delta, sku = :sku, stock = :stock,
+             display_order = :display_order, is_active = :is_active
+         WHERE id = :variant_id",
+        params! {
+            "variant_name" => data.variant_name.trim(),
+            "price_delta" => data.price_delta,
+            "sku" => data.sku.as_deref().map(str::trim).filter(|sku| !sku.is_empty()),
+            "stock" => data.stock,
+            "display_order" => data.display_order,
+            "is_active" => data.is_active,
+            "variant_id" => variant_id,
+        },
+    )
+    .map_err(|e| goods_variant_write_error(&data, e))?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))
+}
+
+/// Variants that were ever sold cannot be deleted; deactivate them instead.
+#[tauri::command]
+pub fn delete_goods_variant(
+    token: String,
+    variant_id: i32,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let order_count: Option<i64> = conn
+        .exec_first(
+            "SELECT COUNT(DISTINCT order_id) FROM order_items WHERE variant_id = :variant_id",
+            params! { "variant_id" => variant_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query variant history", e))?;
+    let order_count = order_count.unwrap_or(0);
+    if order_count > 0 {
+        return Err(CafeHubError::VariantInUse {
+            variant_id,
+            order_count,
+        });
+    }
+
+    conn.exec_drop(
+        "DELETE FROM goods_variant WHERE id = :variant_id",
+        params! { "variant_id" => variant_id },
+    )
+    .map_err(|e| {
+        eprintln!(
+            "Database delete failed for goods variant ID {}: {}",
+            variant_id, e
+        );
+        CafeHubError::database("Database error while deleting goods variant", e)
+    })?;
+
+    if conn.affected_rows() == 0 {
+        return Err(CafeHubError::not_found(Entity::GoodsVariant, variant_id));
+    }
+    Ok(())
+}
+
+/// Every category in menu order.
+fn load_goods_categories<Q: Queryable>(conn: &mut Q) -> Result<Vec<GoodsCategory>, CafeHubError> {
+    conn.query_map(
+        "SELECT id, name, display_order, icon, is_active FROM goods_category
+         ORDER BY display_order ASC, id ASC",
+        |(id, name, display_order, icon, is_active)| GoodsCategory {
+            id,
+            name,
+            display_order,
+            icon,
+            is_active,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Database query failed for goods categories", e))
+}
+
+/// Returns the category's name, or `NotFound` if there is no such category.
+fn ensure_category_exists<Q: Queryable>(
+    conn: &mut Q,
+    category_id: i32,
+) -> Result<String, CafeHubError> {
+    conn.exec_first(
+        "SELECT name FROM goods_category WHERE id = :category_id",
+        params! { "category_id" => category_id },
+    )
+    .map_err(|e| CafeHubError::database("Failed to query goods category", e))?
+    .ok_or_else(|| CafeHubError::not_found(Entity::GoodsCategory, category_id))
+}
+
+fn validate_goods_category(data: &GoodsCategoryData) -> Result<(), CafeHubError> {
+    let name = data.name.trim();
+    if name.is_empty() {
+        return Err(CafeHubError::EmptyField { field: "name" });
+    }
+    if name.chars().count() > 20 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "name",
+            max_length: 20,
+        });
+    }
+    if data
+        .icon
+        .as_deref()
+        .is_some_and(|icon| icon.chars().count() > 255)
+    {
+        return Err(CafeHubError::FieldTooLong {
+            field: "icon",
+            max_length: 255,
+        });
+    }
+    Ok(())
+}
+
+fn goods_category_write_error(data: &GoodsCategoryData, e: MySQLError) -> CafeHubError {
+    if let MySQLError::MySqlError(ref mysql_err) = e {
+        if mysql_err.code == 1062 {
+            return CafeHubError::NameTaken {
+                entity: Entity::GoodsCategory,
+                name: data.name.trim().to_string(),
+            };
+        }
+    }
+    CafeHubError::database("Database error while saving goods category", e)
+}
+
+/// Inactive categories are only listed for staff who manage goods.
+#[tauri::command]
+pub fn get_goods_categories(
+    token: String,
+    include_inactive: Option<bool>,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<GoodsCategory>, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewGoods)?;
+    let include_inactive = include_inactive.unwrap_or(false);
+    if include_inactive {
+        sessions.authorize(&token, Permission::ManageGoods)?;
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut categories = load_goods_categories(&mut conn)?;
+    if !include_inactive {
+        categories.retain(|category| category.is_active);
+    }
+    Ok(categories)
+}
+
+#[tauri::command]
+pub fn create_goods_category(
+    token: String,
+    data: GoodsCategoryData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<i32, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+    validate_goods_category(&data)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    conn.exec_drop(
+        "INSERT INTO goods_category (name, display_order, icon, is_active)
+         VALUES (:name, :display_order, :icon, :is_active)",
+        params! {
+            "name" => data.name.trim(),
+            "display_order" => data.display_order,
+            "icon" => data.icon.as_deref().map(str::trim).filter(|icon| !icon.is_empty()),
+            "is_active" => data.is_active,
+        },
+    )
+    .map_err(|e| goods_category_write_error(&data, e))?;
+
+    Ok(conn.last_insert_id() as i32)
+}
+
+#[tauri::command]
+pub fn update_goods_category(
+    token: String,
+    category_id: i32,
+    data: GoodsCategoryData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+    validate_goods_category(&data)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    ensure_category_exists(&mut conn, category_id)?;
+
+    conn.exec_drop(
+        "UPDATE goods_category
+         SET name = :name, display_order = :display_order, icon = :icon, is_active = :is_active
+         WHERE id = :category_id",
+        params! {
+            "name" => data.name.trim(),
+            "display_order" => data.display_order,
+            "icon" => data.icon.as_deref().map(str::trim).filter(|icon| !icon.is_empty()),
+            "is_active" => data.is_active,
+            "category_id" => category_id,
+        },
+    )
+    .map_err(|e| goods_category_write_error(&data, e))
+}
+
+/// Only empty categories can be deleted; their tax rate goes with them.
+#[tauri::command]
+pub fn delete_goods_category(
+    token: String,
+    category_id: i32,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageGoods)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let goods_count: Option<i64> = conn
+        .exec_first(
+            "SELECT COUNT(*) FROM goods WHERE category_id = :category_id",
+            params! { "category_id" => category_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query goods", e))?;
+    let goods_count = goods_count.unwrap_or(0);
+    if goods_count > 0 {
+        return Err(CafeHubError::CategoryInUse {
+            category_id,
+            goods_count,
+        });
+    }
+
+    conn.exec_drop(
+        "DELETE FROM goods_category WHERE id = :category_id",
+        params! { "category_id" => category_id },
+    )
+    .map_err(|e| {
+        eprintln!(
+            "Database delete failed for goods category ID {}: {}",
+            category_id, e
+        );
+        CafeHubError::database("Database error while deleting goods category", e)
+    })?;
+
+    if conn.affected_rows() == 0 {
+        return Err(CafeHubError::not_found(Entity::GoodsCategory, category_id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn recharge_balance(
+    token: String,
+    data: RechargeBalanceData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<RechargeResult, CafeHubError> {
+    let staff_id = sessions
+        .authorize(&token, Permission::RechargeBalance)?
+        .user_id;
+
+    if data.amount <= Decimal::ZERO {
+        return Err(CafeHubError::NonPositiveAmount { field: "amount" });
+    }
+    let note = data
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.chars().count() > 255) {
+        return Err(CafeHubError::FieldTooLong {
+            field: "note",
+            max_length: 255,
+        });
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let account: Option<(i8, Option<Decimal>)> = tx
+        .exec_first(
+            "SELECT user_type, balance FROM account WHERE id = :user_id FOR UPDATE",
+            params! { "user_id" => data.user_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query user", e))?;
+
+    let balance_before = match account {
+        Some((1, balance)) => balance.unwrap_or(Decimal::ZERO),
+        Some(_) => {
+            return Err(CafeHubError::NotACustomer {
+                user_id: data.user_id,
+            })
+        }
+        None => return Err(CafeHubError::not_found(Entity::User, data.user_id)),
+    };
+    let created_at = Local::now().naive_local();
+
+    // Apply the most generous rule the top-up qualifies for.
+    let rules: Vec<(i64, String, String, Decimal)> = tx
+        .exec(
+            "SELECT id, name, bonus_type, bonus_value FROM recharge_bonus_rules
+             WHERE is_active = 1 AND min_amount <= :amount
+               AND (valid_from IS NULL OR valid_from <= :today)
+               AND (valid_until IS NULL OR valid_until >= :today)
+             ORDER BY id ASC",
+            params! {
+                "amount" => data.amount,
+                "today" => created_at.date(),
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query recharge bonus rules", e))?;
+    let mut bonus: Option<(i64, String, Decimal)> = None;
+    for (rule_id, rule_name, bonus_type, bonus_value) in rules {
+        let Some(bonus_type) = BonusType::from_name(&bonus_type) else {
+            continue;
+        };
+        let amount = recharge_bonus(bonus_type, bonus_value, data.amount);
+        if amount > Decimal::ZERO && bonus.as_ref().is_none_or(|(_, _, best)| amount > *best) {
+            bonus = Some((rule_id, rule_name, amount));
+        }
+    }
+    let bonus_rule_id = bonus.as_ref().map(|(rule_id, _, _)| *rule_id);
+    let bonus_amount = bonus
+        .as_ref()
+        .map_or(Decimal::ZERO, |(_, _, amount)| *amount);
+    let balance_after = balance_before + data.amount + bonus_amount;
+
+    tx.exec_drop(
+        "UPDATE account SET balance = :balance WHERE id = :user_id",
+        params! {
+            "balance" => balance_after,
+            "user_id" => data.user_id,
+        },
+    )
+    .map_err(|e| {
+        eprintln!(
+            "Database update failed for balance recharge (user ID {}): {}",
+            data.user_id, e
+        );
+        CafeHubError::database("Database error while recharging balance", e)
+    })?;
+
+    tx.exec_drop(
+        "INSERT INTO recharges (user_id, amount, bonus_amount, bonus_rule_id, payment_method, note, operator_id, created_at)
+         VALUES (:user_id, :amount, :bonus_amount, :bonus_rule_id, :payment_method, :note, :operator_id, :created_at)",
+        params! {
+            "user_id" => data.user_id,
+            "amount" => data.amount,
+            "bonus_amount" => bonus_amount,
+            "bonus_rule_id" => bonus_rule_id,
+            "payment_method" => data.payment_method.as_str(),
+            "note" => note,
+            "operator_id" => staff_id,
+            "created_at" => created_at,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record recharge", e))?;
+
+    let recharge_id = tx
+        .last_insert_id()
+        .ok_or_else(|| CafeHubError::internal("Failed to read new recharge ID"))?
+        as i64;
+
+    ledger::record(
+        &mut tx,
+        &LedgerEntry {
+            user_id: data.user_id,
+            txn_type: TransactionType::Recharge,
+            amount: data.amount,
+            balance_after: balance_before + data.amount,
+            operator_id: Some(staff_id),
+            order_id: None,
+            refund_id: None,
+            recharge_id: Some(recharge_id),
+            gift_card_id: None,
+            note,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record balance transaction", e))?;
+
+    // The bonus gets its own ledger row so reports can tell paid credit from
+    // promotional credit.
+    if let Some((_, rule_name, _)) = &bonus {
+        ledger::record(
+            &mut tx,
+            &LedgerEntry {
+                user_id: data.user_id,
+                txn_type: TransactionType::RechargeBonus,
+                amount: bonus_amount,
+                balance_after,
+                operator_id: Some(staff_id),
+                order_id: None,
+                refund_id: None,
+                recharge_id: Some(recharge_id),
+                gift_card_id: None,
+                note: Some(rule_name.as_str()),
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to record balance transaction", e))?;
+    }
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    println!(
+        "Staff ID {} recharged {} ({}, bonus {}) for user ID {}",
+        staff_id,
+        data.amount,
+        data.payment_method.as_str(),
+        bonus_amount,
+        data.user_id
+    );
+
+    Ok(RechargeResult {
+        recharge_id,
+        user_id: data.user_id,
+        amount: data.amount,
+        payment_method: data.payment_method,
+        bonus_amount,
+        bonus_rule_id,
+        balance_before,
+        balance_after,
+        operator_id: staff_id,
+        created_at,
+    })
+}
+
+fn recharge_bonus(bonus_type: BonusType, bonus_value: Decimal, amount: Decimal) -> Decimal {
+    match bonus_type {
+        BonusType::Fixed => bonus_value,
+        BonusType::Percent => (amount * bonus_value / Decimal::ONE_HUNDRED).round_dp(2),
+    }
+}
+
+fn validate_bonus_rule(data: &RechargeBonusRuleData) -> Result<(), CafeHubError> {
+    if data.name.trim().is_empty() {
+        return Err(CafeHubError::EmptyField { field: "name" });
+    }
+    if data.name.chars().count() > 50 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "name",
+            max_length: 50,
+        });
+    }
+    if data.min_amount < Decimal::ZERO {
+        return Err(CafeHubError::NegativeValue {
+            field: "min_amount",
+        });
+    }
+    if data.bonus_value <= Decimal::ZERO {
+        return Err(CafeHubError::NonPositiveAmount {
+            field: "bonus_value",
+        });
+    }
+    if data.bonus_type == BonusType::Percent && data.bonus_value > Decimal::ONE_HUNDRED {
+        return Err(CafeHubError::PercentageOutOfRange {
+            field: "bonus_value",
+        });
+    }
+    if let (Some(start_date), Some(end_date)) = (data.valid_from, data.valid_until) {
+        if start_date > end_date {
+            return Err(CafeHubError::InvalidDateRange {
+                start_date,
+                end_date,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_recharge_bonus_rules(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<RechargeBonusRule>, CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let rules = conn
+        .query_map(
+            "SELECT id, name, min_amount, bonus_type, bonus_value, valid_from, valid_until, is_active, created_at
+             FROM recharge_bonus_rules ORDER BY min_amount ASC, id ASC",
+            |(id, name, min_amount, bonus_type, bonus_value, valid_from, valid_until, is_active, created_at): (
+                i64,
+                String,
+                Decimal,
+                String,
+                Decimal,
+                Option<NaiveDate>,
+                Option<NaiveDate>,
+                i8,
+                NaiveDateTime,
+            )| {
+                // The CHECK constraint keeps unknown types out; skip them defensively.
+                BonusType::from_name(&bonus_type).map(|bonus_type| RechargeBonusRule {
+                    id,
+                    name,
+                    min_amount,
+                    bonus_type,
+                    bonus_value,
+                    valid_from,
+                    valid_until,
+                    is_active: is_active == 1,
+                    created_at,
+                })
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for recharge bonus rules", e))?;
+
+    Ok(rules.into_iter().flatten().collect())
+}
+
+#[tauri::command]
+pub fn create_recharge_bonus_rule(
+    token: String,
+    data: RechargeBonusRuleData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<i64, CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+    validate_bonus_rule(&data)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    conn.exec_drop(
+        "INSERT INTO recharge_bonus_rules (name, min_amount, bonus_type, bonus_value, valid_from, valid_until, is_active, created_at)
+         VALUES (:name, :min_amount, :bonus_type, :bonus_value, :valid_from, :valid_until, :is_active, :created_at)",
+        params! {
+            "name" => data.name.trim(),
+            "min_amount" => data.min_amount,
+            "bonus_type" => data.bonus_type.as_str(),
+            "bonus_value" => data.bonus_value,
+            "valid_from" => data.valid_from,
+            "valid_until" => data.valid_until,
+            "is_active" => if data.is_active { 1 } else { 0 },
+            "created_at" => Local::now().naive_local(),
+        },
+    )
+    .map_err(|e| {
+        eprintln!("Database insert failed for recharge bonus rule {}: {}", data.name, e);
+        CafeHubError::database("Database error while creating recharge bonus rule", e)
+    })?;
+
+    Ok(conn.last_insert_id() as i64)
+}
+
+#[tauri::command]
+pub fn update_recharge_bonus_rule(
+    token: String,
+    rule_id: i64,
+    data: RechargeBonusRuleData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+    validate_bonus_rule(&data)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let rule_exists: Option<i64> = conn
+        .exec_first(
+            "SELECT id FROM recharge_bonus_rules WHERE id = :rule_id",
+            params! { "rule_id" => rule_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query recharge bonus rule", e))?;
+    if rule_exists.is_none() {
+        return Err(CafeHubError::not_found(Entity::RechargeBonusRule, rule_id));
+    }
+
+    conn.exec_drop(
+        "UPDATE recharge_bonus_rules
+         SET name = :name, min_amount = :min_amount, bonus_type = :bonus_type, bonus_value = :bonus_value,
+             valid_from = :valid_from, valid_until = :valid_until, is_active = :is_active
+         WHERE id = :rule_id",
+        params! {
+            "name" => data.name.trim(),
+            "min_amount" => data.min_amount,
+            "bonus_type" => data.bonus_type.as_str(),
+            "bonus_value" => data.bonus_value,
+            "valid_from" => data.valid_from,
+            "valid_until" => data.valid_until,
+            "is_active" => if data.is_active { 1 } else { 0 },
+            "rule_id" => rule_id,
+        },
+    )
+    .map_err(|e| {
+        eprintln!("Database update failed for recharge bonus rule ID {}: {}", rule_id, e);
+        CafeHubError::database("Database error while updating recharge bonus rule", e)
+    })
+}
+
+#[tauri::command]
+pub fn delete_recharge_bonus_rule(
+    token: String,
+    rule_id: i64,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    // Past recharges keep their bonus amount; their rule reference is cleared.
+    conn.exec_drop(
+        "DELETE FROM recharge_bonus_rules WHERE id = :rule_id",
+        params! { "rule_id" => rule_id },
+    )
+    .map_err(|e| {
+        eprintln!(
+            "Database delete failed for recharge bonus rule ID {}: {}",
+            rule_id, e
+        );
+        CafeHubError::database("Database error while deleting recharge bonus rule", e)
+    })?;
+
+    if conn.affected_rows() == 0 {
+        return Err(CafeHubError::not_found(Entity::RechargeBonusRule, rule_id));
+    }
+    Ok(())
+}
+
+fn normalize_coupon_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+fn validate_coupon(data: &CouponData) -> Result<(), CafeHubError> {
+    let code = normalize_coupon_code(&data.code);
+    if code.is_empty() {
+        return Err(CafeHubError::EmptyField { field: "code" });
+    }
+    if code.chars().count() > 32 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "code",
+            max_length: 32,
+        });
+    }
+    if data
+        .description
+        .as_deref()
+        .is_some_and(|description| description.trim().chars().count() > 100)
+    {
+        return Err(CafeHubError::FieldTooLong {
+            field: "description",
+            max_length: 100,
+        });
+    }
+
+    match data.discount_type {
+        DiscountType::Percent | DiscountType::Fixed => {
+            let value = data.discount_value.ok_or(CafeHubError::EmptyField {
+                field: "discount_value",
+            })?;
+            if value <= Decimal::ZERO {
+                return Err(CafeHubError::NonPositiveAmount {
+                    field: "discount_value",
+                });
+            }
+            if data.discount_type == DiscountType::Percent && value > Decimal::ONE_HUNDRED {
+                return Err(CafeHubError::PercentageOutOfRange {
+                    field: "discount_value",
+                });
+            }
+        }
+        DiscountType::FreeItem => {
+            if data.free_goods_id.is_none() {
+                return Err(CafeHubError::EmptyField {
+                    field: "free_goods_id",
+                });
+            }
+        }
+    }
+
+    if data
+        .min_spend
+        .is_some_and(|min_spend| min_spend < Decimal::ZERO)
+    {
+        return Err(CafeHubError::NegativeValue { field: "min_spend" });
+    }
+    if data.per_customer_limit.is_some_and(|limit| limit <= 0) {
+        return Err(CafeHubError::NonPositiveAmount {
+            field: "per_customer_limit",
+        });
+    }
+    if let (Some(start_date), Some(end_date)) = (data.valid_from, data.valid_until) {
+        if start_date > end_date {
+            return Err(CafeHubError::InvalidDateRange {
+                start_date,
+                end_date,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Parameters shared by `create_coupon` and `update_coupon`. Values that do
+/// not apply to the discount type are stored as NULL.
+fn coupon_params(data: &CouponData) -> Vec<(String, mysql::Value)> {
+    let is_free_item = data.discount_type == DiscountType::FreeItem;
+    let description = data
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty());
+    vec![
+        ("code".to_string(), normalize_coupon_code(&data.code).into()),
+        ("description".to_string(), description.into()),
+        (
+            "discount_type".to_string(),
+            data.discount_type.as_str().into(),
+        ),
+        (
+            "discount_value".to_string(),
+            data.discount_value.filter(|_| !is_free_item).into(),
+        ),
+        (
+            "free_goods_id".to_string(),
+            data.free_goods_id.filter(|_| is_free_item).into(),
+        ),
+        (
+            "min_spend".to_string(),
+            data.min_spend.unwrap_or(Decimal::ZERO).into(),
+        ),
+        (
+            "per_customer_limit".to_string(),
+            data.per_customer_limit.into(),
+        ),
+        ("valid_from".to_string(), data.valid_from.into()),
+        ("valid_until".to_string(), data.valid_until.into()),
+        (
+            "is_active".to_string(),
+            (if data.is_active { 1 } else { 0 }).into(),
+        ),
+    ]
+}
+
+fn coupon_write_error(data: &CouponData, e: MySQLError) -> CafeHubError {
+    if let MySQLError::MySqlError(ref mysql_err) = e {
+        if mysql_err.code == 1062 {
+            return CafeHubError::NameTaken {
+                entity: Entity::Coupon,
+                name: normalize_coupon_code(&data.code),
+            };
+        }
+        // Foreign key on free_goods_id
+        if mysql_err.code == 1452 {
+            if let Some(goods_id) = data.free_goods_id {
+                return CafeHubError::not_found(Entity::Goods, goods_id);
+            }
+        }
+    }
+    eprintln!("Database write failed for coupon {}: {}", data.code, e);
+    CafeHubError::database("Database error while saving coupon", e)
+}
+
+#[tauri::command]
+pub fn get_coupons(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<Coupon>, CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let coupons = conn
+        .query_map(
+            "SELECT c.id, c.code, c.description, c.discount_type, c.discount_value, c.free_goods_id,
+                    c.min_spend, c.per_customer_limit, c.valid_from, c.valid_until, c.is_active,
+                    (SELECT COUNT(*) FROM coupon_redemptions r WHERE r.coupon_id = c.id)
+             FROM coupons c ORDER BY c.created_at DESC, c.id DESC",
+            |(
+                id,
+                code,
+                description,
+                discount_type,
+                discount_value,
+                free_goods_id,
+                min_spend,
+                per_customer_limit,
+                valid_from,
+                valid_until,
+                is_active,
+                redemption_count,
+            ): (i64, String, _, String, _, _, _, _, _, _, i8, _)| {
+                DiscountType::from_name(&discount_type).map(|discount_type| Coupon {
+                    id,
+                    code,
+                    description,
+                    discount_type,
+                    discount_value,
+                    free_goods_id,
+                    min_spend,
+                    per_customer_limit,
+                    valid_from,
+                    valid_until,
+                    is_active: is_active == 1,
+                    redemption_count,
+                })
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for coupons", e))?;
+
+    Ok(coupons.into_iter().flatten().collect())
+}
+
+#[tauri::command]
+pub fn create_coupon(
+    token: String,
+    data: CouponData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<i64, CafeHubError> {
+    let staff_id = sessions
+        .authorize(&token, Permission::ManagePromotions)?
+        .user_id;
+    validate_coupon(&data)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut query_params = coupon_params(&data);
+    query_params.push(("created_by".to_string(), staff_id.into()));
+    query_params.push(("created_at".to_string(), Local::now().naive_local().into()));
+
+    conn.exec_drop(
+        "INSERT INTO coupons (code, description, discount_type, discount_value, free_goods_id, min_spend,
+                              per_customer_limit, valid_from, valid_until, is_active, created_by, created_at)
+         VALUES (:code, :description, :discount_type, :discount_value, :free_goods_id, :min_spend,
+                 :per_customer_limit, :valid_from, :valid_until, :is_active, :created_by, :created_at)",
+        mysql::Params::from(query_params),
+    )
+    .map_err(|e| coupon_write_error(&data, e))?;
+
+    Ok(conn.last_insert_id() as i64)
+}
+
+#[tauri::command]
+pub fn update_coupon(
+    token: String,
+    coupon_id: i64,
+    data: CouponData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+    validate_coupon(&data)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let coupon_exists: Option<i64> = conn
+        .exec_first(
+            "SELECT id FROM coupons WHERE id = :coupon_id",
+            params! { "coupon_id" => coupon_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query coupon", e))?;
+    if coupon_exists.is_none() {
+        return Err(CafeHubError::not_found(Entity::Coupon, coupon_id));
+    }
+
+    let mut query_params = coupon_params(&data);
+    query_params.push(("coupon_id".to_string(), coupon_id.into()));
+
+    conn.exec_drop(
+        "UPDATE coupons
+         SET code = :code, description = :description, discount_type = :discount_type,
+             discount_value = :discount_value, free_goods_id = :free_goods_id, min_spend = :min_spend,
+             per_customer_limit = :per_customer_limit, valid_from = :valid_from,
+             valid_until = :valid_until, is_active = :is_active
+         WHERE id = :coupon_id",
+        mysql::Params::from(query_params),
+    )
+    .map_err(|e| coupon_write_error(&data, e))
+}
+
+/// Deletes a coupon that was never redeemed. Redeemed coupons stay for the
+/// order history and can only be deactivated.
+#[tauri::command]
+pub fn delete_coupon(
+    token: String,
+    coupon_id: i64,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let redemption_count: Option<i64> = conn
+        .exec_first(
+            "SELECT COUNT(*) FROM coupon_redemptions WHERE coupon_id = :coupon_id",
+            params! { "coupon_id" => coupon_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query coupon redemptions", e))?;
+    let redemption_count = redemption_count.unwrap_or(0);
+    if redemption_count > 0 {
+        return Err(CafeHubError::CouponInUse {
+            coupon_id,
+            redemption_count,
+        });
+    }
+
+    conn.exec_drop(
+        "DELETE FROM coupons WHERE id = :coupon_id",
+        params! { "coupon_id" => coupon_id },
+    )
+    .map_err(|e| {
+        eprintln!("Database delete failed for coupon ID {}: {}", coupon_id, e);
+        CafeHubError::database("Database error while deleting coupon", e)
+    })?;
+
+    if conn.affected_rows() == 0 {
+        return Err(CafeHubError::not_found(Entity::Coupon, coupon_id));
+    }
+    Ok(())
+}
+
+/// A validated, priced line of a sale, read while its goods row is locked.
+struct PricedLine {
+    goods_id: i32,
+    variant_id: Option<i32>,
+    variant_stock: bool, // The variant keeps its own stock
+    quantity: i32,
+    unit_price: Decimal,
+    discount_amount: Decimal,
+    tax_rate: Decimal,
+    tax_amount: Decimal, // Part of line_total
+    line_total: Decimal, // Net of discount_amount, including tax
+}
+
+/// Locks the goods (and variant) rows for `items`, checks stock and prices
+/// each line. Lines drawing on the same stock are checked together.
+fn lock_order_lines(
+    tx: &mut mysql::Transaction,
+    items: &[PurchaseItem],
+) -> Result<Vec<PricedLine>, CafeHubError> {
+    if items.is_empty() {
+        return Err(CafeHubError::NoItems);
+    }
+
+    for item in items {
+        if item.quantity <= 0 {
+            return Err(CafeHubError::NonPositiveAmount { field: "quantity" });
+        }
+    }
+
+    // Units already taken by earlier lines, keyed by goods and, for variants
+    // with their own stock, the variant.
+    let mut reserved: HashMap<(i32, Option<i32>), i32> = HashMap::new();
+    let mut lines: Vec<PricedLine> = Vec::new();
+    for item in items {
+        let goods_info: Option<(Decimal, i32, bool)> = tx
+            .exec_first(
+                "SELECT price, stock, is_active FROM goods WHERE id = :goods_id FOR UPDATE",
+                params! { "goods_id" => item.goods_id },
+            )
+            .map_err(|e| {
+                CafeHubError::database(format!("Failed to query goods ID {}", item.goods_id), e)
+            })?;
+
+        let (mut price_per_item, mut current_stock) = match goods_info {
+            Some((price, stock, true)) => (price, stock),
+            Some((_, _, false)) => {
+                return Err(CafeHubError::GoodsArchived {
+                    goods_id: item.goods_id,
+                });
+            }
+            None => {
+                return Err(CafeHubError::not_found(Entity::Goods, item.goods_id));
+            }
+        };
+
+        let mut variant_stock = false;
+        if let Some(variant_id) = item.variant_id {
+            let variant: Option<(i32, Decimal, Option<i32>, bool)> = tx
+                .exec_first(
+                    "SELECT goods_id, price_delta, stock, is_active FROM goods_variant
+                     WHERE id = :variant_id FOR UPDATE",
+                    params! { "variant_id" => variant_id },
+                )
+                .map_err(|e| {
+                    CafeHubError::database(format!("Failed to query variant ID {}", variant_id), e)
+                })?;
+            match variant {
+                Some((goods_id, price_delta, stock, is_active)) if goods_id == item.goods_id => {
+                    if !is_active {
+                        return Err(CafeHubError::VariantArchived {
+                            goods_id,
+                            variant_id,
+                        });
+                    }
+                    price_per_item += price_delta;
+                    if price_per_item < Decimal::ZERO {
+                        return Err(CafeHubError::VariantPriceNegative {
+                            goods_id,
+                            variant_id,
+                        });
+                    }
+                    if let Some(stock) = stock {
+                        current_stock = stock;
+                        variant_stock = true;
+                    }
+                }
+                _ => return Err(CafeHubError::not_found(Entity::GoodsVariant, variant_id)),
+            }
+        }
+
+        let stock_key = (item.goods_id, item.variant_id.filter(|_| variant_stock));
+        let taken = reserved.entry(stock_key).or_insert(0);
+        if current_stock - *taken < item.quantity {
+            return Err(CafeHubError::OutOfStock {
+                goods_id: item.goods_id,
+                requested: item.quantity,
+                available: current_stock - *taken,
+            });
+        }
+        *taken += item.quantity;
+
+        let tax_rate = tax::rate_for_goods(tx, item.goods_id).map_err(|e| {
+            CafeHubError::database(
+                format!("Failed to query tax rate for goods ID {}", item.goods_id),
+                e,
+            )
+        })?;
+
+        lines.push(PricedLine {
+            goods_id: item.goods_id,
+            variant_id: item.variant_id,
+            variant_stock,
+            quantity: item.quantity,
+            unit_price: price_per_item,
+            discount_amount: Decimal::ZERO,
+            tax_rate,
+            tax_amount: Decimal::ZERO,
+            line_total: price_per_item * Decimal::from(item.quantity),
+        });
+    }
+
+    Ok(lines)
+}
+
+/// Moves `quantity` units into (positive) or out of (negative) stock. Variants
+/// with their own stock are counted there, everything else on the goods row.
+fn adjust_stock(
+    tx: &mut mysql::Transaction,
+    goods_id: i32,
+    stock_variant_id: Option<i32>,
+    quantity: i32,
+) -> Result<(), CafeHubError> {
+    let result = match stock_variant_id {
+        Some(variant_id) => tx.exec_drop(
+            "UPDATE goods_variant SET stock = stock + :quantity WHERE id = :variant_id",
+            params! {
+                "quantity" => quantity,
+                "variant_id" => variant_id,
+            },
+        ),
+        None => tx.exec_drop(
+            "UPDATE goods SET stock = stock + :quantity WHERE id = :goods_id",
+            params! {
+                "quantity" => quantity,
+                "goods_id" => goods_id,
+            },
+        ),
+    };
+    result.map_err(|e| {
+        CafeHubError::database(
+            format!("Failed to update stock for goods ID {}", goods_id),
+            e,
+        )
+    })
+}
+
+/// Takes the sold quantities out of stock and stores the order's lines.
+fn record_order_lines(
+    tx: &mut mysql::Transaction,
+    order_id: i64,
+    lines: &[PricedLine],
+) -> Result<(), CafeHubError> {
+    for line in lines {
+        adjust_stock(
+            tx,
+            line.goods_id,
+            line.variant_id.filter(|_| line.variant_stock),
+            -line.quantity,
+        )?;
+    }
+
+    tx.exec_batch(
+        "INSERT INTO order_items (order_id, goods_id, variant_id, quantity, unit_price, discount_amount, tax_rate, tax_amount, line_total)
+         VALUES (:order_id, :goods_id, :variant_id, :quantity, :unit_price, :discount_amount, :tax_rate, :tax_amount, :line_total)",
+        lines.iter().map(|line| {
+            params! {
+                "order_id" => order_id,
+                "goods_id" => line.goods_id,
+                "variant_id" => line.variant_id,
+                "quantity" => line.quantity,
+                "unit_price" => line.unit_price,
+                "discount_amount" => line.discount_amount,
+                "tax_rate" => line.tax_rate,
+                "tax_amount" => line.tax_amount,
+                "line_total" => line.line_total,
+            }
+        }),
+    )
+    .map_err(|e| CafeHubError::database("Failed to record order items", e))
+}
+
+/// Works out each line's tax once discounts are applied. Exclusive tax is
+/// added to the line; inclusive tax is the share of it that is tax. Returns
+/// the order's tax.
+fn apply_tax(lines: &mut [PricedLine], prices_include_tax: bool) -> Decimal {
+    for line in lines.iter_mut() {
+        line.tax_amount = tax::line_tax(line.line_total, line.tax_rate, prices_include_tax);
+        if !prices_include_tax {
+            line.line_total += line.tax_amount;
+        }
+    }
+    lines.iter().map(|line| line.tax_amount).sum()
+}
+
+/// Spreads an order-level discount over the lines in proportion to their
+/// totals, so refunds of single lines give back the right share.
+fn allocate_discount(lines: &mut [PricedLine], discount: Decimal) {
+    let subtotal: Decimal = lines.iter().map(|line| line.line_total).sum();
+    if discount <= Decimal::ZERO || subtotal <= Decimal::ZERO {
+        return;
+    }
+    let mut remaining = discount.min(subtotal);
+    let last = lines.len() - 1;
+    for (index, line) in lines.iter_mut().enumerate() {
+        let share = if index == last {
+            remaining
+        } else {
+            (discount * line.line_total / subtotal).round_dp(2)
+        };
+        let share = share.min(line.line_total).min(remaining);
+        line.discount_amount += share;
+        line.line_total -= share;
+        remaining -= share;
+    }
+    // Rounding can leave a cent unplaced; give it to the first line with room.
+    for line in lines.iter_mut() {
+        if remaining <= Decimal::ZERO {
+            break;
+        }
+        let share = remaining.min(line.line_total);
+        line.discount_amount += share;
+        line.line_total -= share;
+        remaining -= share;
+    }
+}
+
+/// Validates `code` for `user_id` with the coupon row locked, so concurrent
+/// checkouts cannot exceed its usage limit, and applies its discount to
+/// `lines`. Returns the coupon ID and the discount.
+fn apply_coupon(
+    tx: &mut mysql::Transaction,
+    code: &str,
+    user_id: i64,
+    lines: &mut [PricedLine],
+) -> Result<(i64, Decimal), CafeHubError> {
+    struct CouponRule {
+        id: i64,
+        discount_type: Option<DiscountType>,
+        discount_value: Option<Decimal>,
+        free_goods_id: Option<i32>,
+        min_spend: Decimal,
+        per_customer_limit: Option<i32>,
+        valid_from: Option<NaiveDate>,
+        valid_until: Option<NaiveDate>,
+    }
+    let coupon = tx
+        .exec_map(
+            "SELECT id, discount_type, discount_value, free_goods_id, min_spend, per_customer_limit, valid_from, valid_until
+             FROM coupons WHERE code = :code AND is_active = 1 FOR UPDATE",
+            params! { "code" => code },
+            |(
+                id,
+                discount_type,
+                discount_value,
+                free_goods_id,
+                min_spend,
+                per_customer_limit,
+                valid_from,
+                valid_until,
+            ): (i64, String, _, _, _, _, _, _)| CouponRule {
+                id,
+                discount_type: DiscountType::from_name(&discount_type),
+                discount_value,
+                free_goods_id,
+                min_spend,
+                per_customer_limit,
+                valid_from,
+                valid_until,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query coupon", e))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| CafeHubError::CouponInvalid {
+            code: code.to_string(),
+        })?;
+
+    let today = Local::now().date_naive();
+    if coupon
+        .valid_from
+        .is_some_and(|valid_from| valid_from > today)
+    {
+        return Err(CafeHubError::CouponInvalid {
+            code: code.to_string(),
+        });
+    }
+    if let Some(valid_until) = coupon
+        .valid_until
+        .filter(|valid_until| *valid_until < today)
+    {
+        return Err(CafeHubError::CouponExpired {
+            code: code.to_string(),
+            valid_until,
+        });
+    }
+
+    let subtotal: Decimal = lines.iter().map(|line| line.line_total).sum();
+    if subtotal < coupon.min_spend {
+        return Err(CafeHubError::CouponMinSpendNotMet {
+            min_spend: coupon.min_spend,
+            subtotal,
+        });
+    }
+
+    if let Some(limit) = coupon.per_customer_limit {
+        let used: Option<i64> = tx
+            .exec_first(
+                "SELECT COUNT(*) FROM coupon_redemptions WHERE coupon_id = :coupon_id AND user_id = :user_id",
+                params! {
+                    "coupon_id" => coupon.id,
+                    "user_id" => user_id,
+                },
+            )
+            .map_err(|e| CafeHubError::database("Failed to query coupon redemptions", e))?;
+        if used.unwrap_or(0) >= i64::from(limit) {
+            return Err(CafeHubError::CouponLimitReached {
+                code: code.to_string(),
+                limit,
+            });
+        }
+    }
+
+    let discount = match (coupon.discount_type, coupon.discount_value) {
+        (Some(DiscountType::Percent), Some(percent)) => {
+            let discount = (subtotal * percent / Decimal::ONE_HUNDRED).round_dp(2);
+            allocate_discount(lines, discount);
+            discount
+        }
+        (Some(DiscountType::Fixed), Some(amount)) => {
+            let discount = amount.min(subtotal);
+            allocate_discount(lines, discount);
+            discount
+        }
+        (Some(DiscountType::FreeItem), _) => {
+            let line = lines
+                .iter_mut()
+                .find(|line| Some(line.goods_id) == coupon.free_goods_id)
+                .ok_or_else(|| CafeHubError::CouponNotApplicable {
+                    code: code.to_string(),
+                })?;
+            // The tier discount may already have taken part of the line.
+            let discount = line.unit_price.min(line.line_total);
+            line.discount_amount += discount;
+            line.line_total -= discount;
+            discount
+        }
+        _ => {
+            return Err(CafeHubError::CouponInvalid {
+                code: code.to_string(),
+            })
+        }
+    };
+
+    Ok((coupon.id, discount))
+}
+
+/// Applies a points redemption to `lines` and returns the points it costs.
+fn apply_points_redemption(
+    tx: &mut mysql::Transaction,
+    redemption: &PointsRedemption,
+    available: i32,
+    lines: &mut [PricedLine],
+) -> Result<i32, CafeHubError> {
+    match *redemption {
+        PointsRedemption::Discount { points } => {
+            if points <= 0 {
+                return Err(CafeHubError::NonPositiveAmount { field: "points" });
+            }
+            let remaining: Decimal = lines.iter().map(|line| line.line_total).sum();
+            let max_points = (remaining / loyalty::POINT_VALUE)
+                .floor()
+                .to_i32()
+                .unwrap_or(i32::MAX);
+            if points > max_points {
+                return Err(CafeHubError::PointsExceedTotal { points, max_points });
+            }
+            if points > available {
+                return Err(CafeHubError::InsufficientPoints {
+                    required: points,
+                    available,
+                });
+            }
+            allocate_discount(lines, Decimal::from(points) * loyalty::POINT_VALUE);
+            Ok(points)
+        }
+        PointsRedemption::FreeItem { goods_id } => {
+            let points_price: Option<i32> = tx
+                .exec_first(
+                    "SELECT points_price FROM goods WHERE id = :goods_id",
+                    params! { "goods_id" => goods_id },
+                )
+                .map_err(|e| CafeHubError::database("Failed to query goods points price", e))?
+                .flatten();
+            let line = lines
+                .iter_mut()
+                .find(|line| line.goods_id == goods_id && line.line_total > Decimal::ZERO);
+            let (Some(points), Some(line)) = (points_price, line) else {
+                return Err(CafeHubError::PointsNotRedeemable { goods_id });
+            };
+            if points > available {
+                return Err(CafeHubError::InsufficientPoints {
+                    required: points,
+                    available,
+                });
+            }
+            let discount = line.unit_price.min(line.line_total);
+            line.discount_amount += discount;
+            line.line_total -= discount;
+            Ok(points)
+        }
+    }
+}
+
+/// Expires lapsed points for `user_id` so reads show what can still be
+/// spent. Returns None if the account does not exist.
+fn refresh_points(conn: &mut mysql::PooledConn, user_id: i64) -> Result<Option<i32>, CafeHubError> {
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let account_exists: Option<i64> = tx
+        .exec_first(
+            "SELECT id FROM account WHERE id = :user_id FOR UPDATE",
+            params! { "user_id" => user_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query user", e))?;
+    if account_exists.is_none() {
+        return Ok(None);
+    }
+
+    let points = loyalty::expire_points(&mut tx, user_id)
+        .map_err(|e| CafeHubError::database("Failed to expire points", e))?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    Ok(Some(points))
+}
+
+/// Checks that the tenders cover `total` exactly and returns the part paid
+/// from the member balance.
+fn validate_tenders(tenders: &[Tender], total: Decimal) -> Result<Decimal, CafeHubError> {
+    if tenders.iter().any(|tender| tender.amount <= Decimal::ZERO) {
+        return Err(CafeHubError::NonPositiveAmount { field: "amount" });
+    }
+    let tendered: Decimal = tenders.iter().map(|tender| tender.amount).sum();
+    if tendered != total {
+        return Err(CafeHubError::TenderMismatch { total, tendered });
+    }
+    Ok(tenders
+        .iter()
+        .filter(|tender| tender.tender_type == TenderType::Balance)
+        .map(|tender| tender.amount)
+        .sum())
+}
+
+/// Value for `orders.payment_method`: the single tender type used, or `split`.
+fn order_payment_method(tenders: &[Tender]) -> &'static str {
+    match tenders.split_first() {
+        Some((first, rest)) if rest.iter().all(|t| t.tender_type == first.tender_type) => {
+            first.tender_type.as_str()
+        }
+        Some(_) => "split",
+        None => TenderType::Balance.as_str(),
+    }
+}
+
+/// What a gift card tender takes off its card.
+struct GiftCardDebit {
+    gift_card_id: i64,
+    amount: Decimal,
+    balance_after: Decimal,
+}
+
+/// Locks the card behind every gift card tender and checks it can cover
+/// the amount. A card used in several tenders must cover their sum. The
+/// result lines up with `tenders`.
+fn lock_gift_card_tenders(
+    tx: &mut mysql::Transaction,
+    tenders: &[Tender],
+) -> Result<Vec<Option<GiftCardDebit>>, CafeHubError> {
+    let mut balances: HashMap<i64, Decimal> = HashMap::new();
+    let mut debits = Vec::with_capacity(tenders.len());
+    for tender in tenders {
+        if tender.tender_type != TenderType::GiftCard {
+            debits.push(None);
+            continue;
+        }
+        let code = tender
+            .gift_card_code
+            .as_deref()
+            .map(gift_cards::normalize_code)
+            .filter(|code| !code.is_empty())
+            .ok_or(CafeHubError::EmptyField {
+                field: "gift_card_code",
+            })?;
+        let (gift_card_id, balance): (i64, Decimal) = tx
+            .exec_first(
+                "SELECT id, balance FROM gift_cards WHERE code = :code FOR UPDATE",
+                params! { "code" => &code },
+            )
+            .map_err(|e| CafeHubError::database("Failed to query gift card", e))?
+            .ok_or_else(|| CafeHubError::GiftCardNotFound { code: code.clone() })?;
+        let available = *balances.entry(gift_card_id).or_insert(balance);
+        if available < tender.amount {
+            return Err(CafeHubError::GiftCardInsufficient {
+                code,
+                required: tender.amount,
+                available,
+            });
+        }
+        let balance_after = available - tender.amount;
+        balances.insert(gift_card_id, balance_after);
+        debits.push(Some(GiftCardDebit {
+            gift_card_id,
+            amount: tender.amount,
+            balance_after,
+        }));
+    }
+    Ok(debits)
+}
+
+/// Inserts the order's tenders and takes gift card tenders off their cards.
+/// `gift_cards` comes from `lock_gift_card_tenders`, or is empty when the
+/// order has none.
+fn record_order_tenders(
+    tx: &mut mysql::Transaction,
+    order_id: i64,
+    user_id: Option<i64>,
+    operator_id: Option<i64>,
+    tenders: &[Tender],
+    gift_cards: &[Option<GiftCardDebit>],
+) -> Result<(), CafeHubError> {
+    for (index, tender) in tenders.iter().enumerate() {
+        let debit = gift_cards.get(index).and_then(Option::as_ref);
+        if let Some(debit) = debit {
+            gift_cards::record(
+                tx,
+                &gift_cards::GiftCardEntry {
+                    gift_card_id: debit.gift_card_id,
+                    txn_type: GiftCardTxnType::Redeem,
+                    amount: -debit.amount,
+                    balance_after: debit.balance_after,
+                    payment_method: None,
+                    order_id: Some(order_id),
+                    refund_id: None,
+                    user_id,
+                    operator_id,
+                },
+            )
+            .map_err(|e| CafeHubError::database("Failed to record gift card transaction", e))?;
+        }
+        tx.exec_drop(
+            "INSERT INTO order_tenders (order_id, tender_type, amount, gift_card_id) VALUES (:order_id, :tender_type, :amount, :gift_card_id)",
+            params! {
+                "order_id" => order_id,
+                "tender_type" => tender.tender_type.as_str(),
+                "amount" => tender.amount,
+                "gift_card_id" => debit.map(|debit| debit.gift_card_id),
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to record order tenders", e))?;
+    }
+    Ok(())
+}
+
+fn validate_tax_rate(field: &'static str, rate_percent: Decimal) -> Result<(), CafeHubError> {
+    if rate_percent < Decimal::ZERO || rate_percent > Decimal::ONE_HUNDRED {
+        return Err(CafeHubError::PercentageOutOfRange { field });
+    }
+    Ok(())
+}
+
+/// Validates `data` and returns the name of its category.
+fn validate_tax_rate_data<Q: Queryable>(
+    conn: &mut Q,
+    data: &TaxRateData,
+) -> Result<String, CafeHubError> {
+    validate_tax_rate("rate_percent", data.rate_percent)?;
+    ensure_category_exists(conn, data.category_id)
+}
+
+fn tax_rate_write_error(category_name: String, e: MySQLError) -> CafeHubError {
+    if let MySQLError::MySqlError(ref mysql_err) = e {
+        // One rate per category
+        if mysql_err.code == 1062 {
+            return CafeHubError::NameTaken {
+                entity: Entity::TaxRate,
+                name: category_name,
+            };
+        }
+    }
+    CafeHubError::database("Database error while saving tax rate", e)
+}
+
+/// The pricing mode, default rate and per-category rates. Staff need these
+/// to quote prices, so any signed-in user may read them.
+#[tauri::command]
+pub fn get_tax_settings(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<TaxSettings, CafeHubError> {
+    sessions.authenticate(&token)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let (prices_include_tax, default_rate_percent): (bool, Decimal) = conn
+        .query_first(
+            "SELECT prices_include_tax, default_rate_percent FROM tax_settings WHERE id = 1",
+        )
+        .map_err(|e| CafeHubError::database("Failed to query tax settings", e))?
+        .unwrap_or((true, Decimal::ZERO));
+
+    let rates = conn
+        .query_map(
+            "SELECT r.id, r.category_id, c.name, r.rate_percent
+             FROM tax_rates r
+             JOIN goods_category c ON r.category_id = c.id
+             ORDER BY c.display_order ASC, c.id ASC",
+            |(id, category_id, category_name, rate_percent)| TaxRate {
+                id,
+                category_id,
+                category_name,
+                rate_percent,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for tax rates", e))?;
+
+    Ok(TaxSettings {
+        prices_include_tax,
+        default_rate_percent,
+        rates,
+    })
+}
+
+/// Switches between tax-inclusive and tax-exclusive prices. Only affects
+/// orders placed afterwards; past orders keep the mode they were priced in.
+#[tauri::command]
+pub fn update_tax_settings(
+    token: String,
+    data: UpdateTaxSettingsData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageTaxes)?;
+    validate_tax_rate("default_rate_percent", data.default_rate_percent)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    conn.exec_drop(
+        "INSERT INTO tax_settings (id, prices_include_tax, default_rate_percent)
+         VALUES (1, :prices_include_tax, :default_rate_percent)
+         ON DUPLICATE KEY UPDATE prices_include_tax = VALUES(prices_include_tax), default_rate_percent = VALUES(default_rate_percent)",
+        params! {
+            "prices_include_tax" => data.prices_include_tax,
+            "default_rate_percent" => data.default_rate_percent,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Database error while updating tax settings", e))
+}
+
+#[tauri::command]
+pub fn create_tax_rate(
+    token: String,
+    data: TaxRateData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<i32, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageTaxes)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let category_name = validate_tax_rate_data(&mut conn, &data)?;
+
+    conn.exec_drop(
+        "INSERT INTO tax_rates (category_id, rate_percent) VALUES (:category_id, :rate_percent)",
+        params! {
+            "category_id" => data.category_id,
+            "rate_percent" => data.rate_percent,
+        },
+    )
+    .map_err(|e| tax_rate_write_error(category_name, e))?;
+
+    Ok(conn.last_insert_id() as i32)
+}
+
+#[tauri::command]
+pub fn update_tax_rate(
+    token: String,
+    rate_id: i32,
+    data: TaxRateData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageTaxes)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let category_name = validate_tax_rate_data(&mut conn, &data)?;
+
+    let rate_exists: Option<i32> = conn
+        .exec_first(
+            "SELECT id FROM tax_rates WHERE id = :rate_id",
+            params! { "rate_id" => rate_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query tax rate", e))?;
+    if rate_exists.is_none() {
+        return Err(CafeHubError::not_found(Entity::TaxRate, rate_id));
+    }
+
+    conn.exec_drop(
+        "UPDATE tax_rates SET category_id = :category_id, rate_percent = :rate_percent WHERE id = :rate_id",
+        params! {
+            "category_id" => data.category_id,
+            "rate_percent" => data.rate_percent,
+            "rate_id" => rate_id,
+        },
+    )
+    .map_err(|e| tax_rate_write_error(category_name, e))
+}
+
+/// Goods of the category fall back to the default rate. Past orders keep
+/// the rate they were taxed at.
+#[tauri::command]
+pub fn delete_tax_rate(
+    token: String,
+    rate_id: i32,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManageTaxes)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    conn.exec_drop(
+        "DELETE FROM tax_rates WHERE id = :rate_id",
+        params! { "rate_id" => rate_id },
+    )
+    .map_err(|e| CafeHubError::database("Database error while deleting tax rate", e))?;
+
+    if conn.affected_rows() == 0 {
+        return Err(CafeHubError::not_found(Entity::TaxRate, rate_id));
+    }
+    Ok(())
+}
+
+/// Sales and tax collected per rate over an inclusive date range, net of
+/// refunds. A refunded part of a
+/// line gives back the same share of its tax.
+#[tauri::command]
+pub fn get_tax_report(
+    token: String,
+    data: TaxReportQuery,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<TaxReport, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewAnalytics)?;
+
+    let mut conditions: Vec<String> = Vec::new();
+    let mut query_params: Vec<(String, mysql::Value)> = Vec::new();
+    push_date_range(
+        "o.created_at",
+        Some(data.start_date),
+        Some(data.end_date),
+        &mut conditions,
+        &mut query_params,
+    )?;
+    let where_clause = conditions.join(" AND ");
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let query = format!(
+        "SELECT oi.tax_rate,
+                SUM(oi.line_total - oi.refunded_amount),
+                SUM(CASE WHEN oi.line_total > 0
+                         THEN oi.tax_amount * (oi.line_total - oi.refunded_amount) / oi.line_total
+                         ELSE 0 END)
+         FROM order_items oi
+         JOIN orders o ON oi.order_id = o.id
+         WHERE {}
+         GROUP BY oi.tax_rate
+         ORDER BY oi.tax_rate ASC",
+        where_clause
+    );
+
+    let lines: Vec<TaxReportLine> = conn
+        .exec_map(
+            query,
+            mysql::Params::from(query_params),
+            |(tax_rate, sales_amount, tax_amount): (Decimal, Decimal, Decimal)| {
+                let tax_amount = tax_amount.round_dp(2);
+                TaxReportLine {
+                    tax_rate,
+                    sales_amount,
+                    net_amount: sales_amount - tax_amount,
+                    tax_amount,
+                }
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for tax report", e))?;
+
+    Ok(TaxReport {
+        start_date: data.start_date,
+        end_date: data.end_date,
+        sales_amount: lines.iter().map(|line| line.sales_amount).sum(),
+        net_amount: lines.iter().map(|line| line.net_amount).sum(),
+        tax_amount: lines.iter().map(|line| line.tax_amount).sum(),
+        lines,
+    })
+}
+
+#[tauri::command]
+pub fn purchase_goods(
+    token: String,
+    data: PurchaseGoodsData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<PurchaseResult, CafeHubError> {
+    let user_id = sessions.authorize(&token, Permission::Purchase)?.user_id;
+
+    // Nothing here can confirm cash, card or transfer payments were actually
+    // received; staff take those through walk_in_checkout.
+    if let Some(tender) = data.tenders.iter().flatten().find(|tender| {
+        !matches!(
+            tender.tender_type,
+            TenderType::Balance | TenderType::GiftCard
+        )
+    }) {
+        return Err(CafeHubError::TenderNotAllowed {
+            tender_type: tender.tender_type.as_str(),
+        });
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let mut lines = lock_order_lines(&mut tx, &data.items)?;
+    let subtotal: Decimal = lines.iter().map(|line| line.line_total).sum();
+
+    let user_info: Option<(Decimal, Option<Decimal>)> = tx
+        .exec_first(
+            "SELECT a.balance, t.discount_percent FROM account a
+             LEFT JOIN membership_tiers t ON a.tier_id = t.id
+             WHERE a.id = :user_id AND a.user_type = 1 FOR UPDATE",
+            params! { "user_id" => user_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query user", e))?;
+
+    let (current_balance, tier_percent) = match user_info {
+        Some(info) => info,
+        None => {
+            return Err(CafeHubError::not_found(Entity::Customer, user_id));
+        }
+    };
+
+    // Members get their tier discount first; coupons and points apply to
+    // the member price.
+    let tier_discount = match tier_percent {
+        Some(percent) if percent > Decimal::ZERO => {
+            let discount = (subtotal * percent / Decimal::ONE_HUNDRED).round_dp(2);
+            allocate_discount(&mut lines, discount);
+            discount
+        }
+        _ => Decimal::ZERO,
+    };
+
+    let coupon_code = data
+        .coupon_code
+        .as_deref()
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .map(str::to_uppercase);
+    let (coupon_id, coupon_discount) = match &coupon_code {
+        Some(code) => {
+            let (coupon_id, discount) = apply_coupon(&mut tx, code, user_id, &mut lines)?;
+            (Some(coupon_id), discount)
+        }
+        None => (None, Decimal::ZERO),
+    };
+
+    let points_before = loyalty::expire_points(&mut tx, user_id)
+        .map_err(|e| CafeHubError::database("Failed to expire points", e))?;
+    let points_redeemed = match &data.redeem_points {
+        Some(redemption) => {
+            apply_points_redemption(&mut tx, redemption, points_before, &mut lines)?
+        }
+        None => 0,
+    };
+
+    let discount_amount = subtotal - lines.iter().map(|line| line.line_total).sum::<Decimal>();
+    let prices_include_tax = tax::prices_include_tax(&mut tx)
+        .map_err(|e| CafeHubError::database("Failed to query tax settings", e))?;
+    let tax_amount = apply_tax(&mut lines, prices_include_tax);
+    let total_purchase_price: Decimal = lines.iter().map(|line| line.line_total).sum();
+    let points_earned = loyalty::points_for(total_purchase_price);
+    let points_after = points_before - points_redeemed + points_earned;
+
+    let tenders = match data.tenders {
+        Some(tenders) => tenders,
+        None if total_purchase_price > Decimal::ZERO => vec![Tender {
+            tender_type: TenderType::Balance,
+            amount: total_purchase_price,
+            gift_card_code: None,
+        }],
+        None => Vec::new(),
+    };
+    let balance_paid = validate_tenders(&tenders, total_purchase_price)?;
+    let gift_card_debits = lock_gift_card_tenders(&mut tx, &tenders)?;
+
+    if current_balance < balance_paid {
+        return Err(CafeHubError::InsufficientBalance {
+            required: balance_paid,
+            available: current_balance,
+            shortfall: balance_paid - current_balance,
+        });
+    }
+
+    let balance_after = current_balance - balance_paid;
+    let created_at = Local::now().naive_local();
+    if balance_paid > Decimal::ZERO {
+        tx.exec_drop(
+            "UPDATE account SET balance = :balance WHERE id = :user_id",
+            params! {
+                "balance" => balance_after,
+                "user_id" => user_id,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to update user balance", e))?;
+    }
+    if points_after != points_before {
+        tx.exec_drop(
+            "UPDATE account SET points = :points WHERE id = :user_id",
+            params! {
+                "points" => points_after,
+                "user_id" => user_id,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to update user points", e))?;
+    }
+
+    tx.exec_drop(
+        "INSERT INTO orders (user_id, subtotal, discount_amount, tax_amount, prices_include_tax, tier_discount, coupon_id, points_redeemed, points_earned, total_amount, payment_method, balance_before, balance_after, created_at)
+         VALUES (:user_id, :subtotal, :discount_amount, :tax_amount, :prices_include_tax, :tier_discount, :coupon_id, :points_redeemed, :points_earned, :total_amount, :payment_method, :balance_before, :balance_after, :created_at)",
+        params! {
+            "user_id" => user_id,
+            "subtotal" => subtotal,
+            "discount_amount" => discount_amount,
+            "tax_amount" => tax_amount,
+            "prices_include_tax" => prices_include_tax,
+            "tier_discount" => tier_discount,
+            "coupon_id" => coupon_id,
+            "points_redeemed" => points_redeemed,
+            "points_earned" => points_earned,
+            "total_amount" => total_purchase_price,
+            "payment_method" => order_payment_method(&tenders),
+            "balance_before" => current_balance,
+            "balance_after" => balance_after,
+            "created_at" => created_at,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record order", e))?;
+
+    let order_id =
+        tx.last_insert_id()
+            .ok_or_else(|| CafeHubError::internal("Failed to read new order ID"))? as i64;
+
+    if let Some(coupon_id) = coupon_id {
+        tx.exec_drop(
+            "INSERT INTO coupon_redemptions (coupon_id, order_id, user_id, discount_amount, created_at)
+             VALUES (:coupon_id, :order_id, :user_id, :discount_amount, :created_at)",
+            params! {
+                "coupon_id" => coupon_id,
+                "order_id" => order_id,
+                "user_id" => user_id,
+                "discount_amount" => coupon_discount,
+                "created_at" => created_at,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to record coupon redemption", e))?;
+    }
+
+    if balance_paid > Decimal::ZERO {
+        ledger::record(
+            &mut tx,
+            &LedgerEntry {
+                user_id,
+                txn_type: TransactionType::Purchase,
+                amount: -balance_paid,
+                balance_after,
+                operator_id: Some(user_id),
+                order_id: Some(order_id),
+                refund_id: None,
+                recharge_id: None,
+                gift_card_id: None,
+                note: None,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to record balance transaction", e))?;
+    }
+
+    if points_redeemed > 0 {
+        loyalty::record(
+            &mut tx,
+            &loyalty::PointsEntry {
+                user_id,
+                txn_type: PointsTxnType::Redeem,
+                points: -points_redeemed,
+                points_after: points_before - points_redeemed,
+                operator_id: Some(user_id),
+                order_id: Some(order_id),
+                note: None,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to record points transaction", e))?;
+    }
+    if points_earned > 0 {
+        loyalty::record(
+            &mut tx,
+            &loyalty::PointsEntry {
+                user_id,
+                txn_type: PointsTxnType::Earn,
+                points: points_earned,
+                points_after,
+                operator_id: Some(user_id),
+                order_id: Some(order_id),
+                note: None,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to record points transaction", e))?;
+    }
+
+    record_order_lines(&mut tx, order_id, &lines)?;
+    record_order_tenders(
+        &mut tx,
+        order_id,
+        Some(user_id),
+        Some(user_id),
+        &tenders,
+        &gift_card_debits,
+    )?;
+
+    tiers::recompute_tier(&mut tx, user_id)
+        .map_err(|e| CafeHubError::database("Failed to update membership tier", e))?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    Ok(PurchaseResult {
+        order_id,
+        item_count: lines.iter().map(|line| line.quantity).sum(),
+        subtotal,
+        discount_amount,
+        tier_discount,
+        tax_amount,
+        prices_include_tax,
+        total_amount: total_purchase_price,
+        coupon_code,
+        tenders,
+        balance_after,
+        points_redeemed,
+        points_earned,
+        points_after,
+    })
+}
+
+/// Checkout at the till, run by staff. The sale is recorded as an order with
+/// no user unless a member is named, whose balance can then be a tender.
+#[tauri::command]
+pub fn walk_in_checkout(
+    token: String,
+    data: WalkInCheckoutData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<WalkInCheckoutResult, CafeHubError> {
+    let cashier_id = sessions.authorize(&token, Permission::WalkInSales)?.user_id;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let mut lines = lock_order_lines(&mut tx, &data.items)?;
+    let subtotal: Decimal = lines.iter().map(|line| line.line_total).sum();
+    let prices_include_tax = tax::prices_include_tax(&mut tx)
+        .map_err(|e| CafeHubError::database("Failed to query tax settings", e))?;
+    let tax_amount = apply_tax(&mut lines, prices_include_tax);
+    let total_amount: Decimal = lines.iter().map(|line| line.line_total).sum();
+
+    let balance_paid = validate_tenders(&data.tenders, total_amount)?;
+    if balance_paid > Decimal::ZERO && data.customer_id.is_none() {
+        return Err(CafeHubError::TenderNotAllowed {
+            tender_type: TenderType::Balance.as_str(),
+        });
+    }
+    let gift_card_debits = lock_gift_card_tenders(&mut tx, &data.tenders)?;
+
+    let cash_paid: Decimal = data
+        .tenders
+        .iter()
+        .filter(|tender| tender.tender_type == TenderType::Cash)
+        .map(|tender| tender.amount)
+        .sum();
+    let (cash_tendered, change_due) = if cash_paid > Decimal::ZERO {
+        let tendered = data.cash_tendered.unwrap_or(cash_paid);
+        if tendered < cash_paid {
+            return Err(CafeHubError::InsufficientCash {
+                required: cash_paid,
+                tendered,
+            });
+        }
+        (Some(tendered), Some(tendered - cash_paid))
+    } else {
+        (None, None)
+    };
+
+    let balances = match data.customer_id {
+        Some(customer_id) => {
+            let current_balance: Decimal = tx
+                .exec_first(
+                    "SELECT balance FROM account WHERE id = :user_id AND user_type = 1 FOR UPDATE",
+                    params! { "user_id" => customer_id },
+                )
+                .map_err(|e| CafeHubError::database("Failed to query customer", e))?
+                .ok_or_else(|| CafeHubError::not_found(Entity::Customer, customer_id))?;
+            if current_balance < balance_paid {
+                return Err(CafeHubError::InsufficientBalance {
+                    required: balance_paid,
+                    available: current_balance,
+                    shortfall: balance_paid - current_balance,
+                });
+            }
+            Some((current_balance, current_balance - balance_paid))
+        }
+        None => None,
+    };
+
+    let payment_method = order_payment_method(&data.tenders);
+
+    tx.exec_drop(
+        "INSERT INTO orders (user_id, subtotal, discount_amount, tax_amount, prices_include_tax, total_amount, payment_method, cash_tendered, change_due, cashier_id, balance_before, balance_after, created_at)
+         VALUES (:user_id, :subtotal, :discount_amount, :tax_amount, :prices_include_tax, :total_amount, :payment_method, :cash_tendered, :change_due, :cashier_id, :balance_before, :balance_after, :created_at)",
+        params! {
+            "user_id" => data.customer_id,
+            "subtotal" => subtotal,
+            "discount_amount" => Decimal::ZERO,
+            "tax_amount" => tax_amount,
+            "prices_include_tax" => prices_include_tax,
+            "total_amount" => total_amount,
+            "payment_method" => payment_method,
+            "cash_tendered" => cash_tendered,
+            "change_due" => change_due,
+            "cashier_id" => cashier_id,
+            "balance_before" => balances.map(|(before, _)| before),
+            "balance_after" => balances.map(|(_, after)| after),
+            "created_at" => Local::now().naive_local(),
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record order", e))?;
+
+    let order_id =
+        tx.last_insert_id()
+            .ok_or_else(|| CafeHubError::internal("Failed to read new order ID"))? as i64;
+
+    if let (Some(customer_id), Some((_, balance_after))) = (data.customer_id, balances) {
+        if balance_paid > Decimal::ZERO {
+            tx.exec_drop(
+                "UPDATE account SET balance = :balance WHERE id = :user_id",
+                params! {
+                    "balance" => balance_after,
+                    "user_id" => customer_id,
+                },
+            )
+            .map_err(|e| CafeHubError::database("Failed to update user balance", e))?;
+
+            ledger::record(
+                &mut tx,
+                &LedgerEntry {
+                    user_id: customer_id,
+                    txn_type: TransactionType::Purchase,
+                    amount: -balance_paid,
+                    balance_after,
+                    operator_id: Some(cashier_id),
+                    order_id: Some(order_id),
+                    refund_id: None,
+                    recharge_id: None,
+                    gift_card_id: None,
+                    note: None,
+                },
+            )
+            .map_err(|e| CafeHubError::database("Failed to record balance transaction", e))?;
+        }
+    }
+
+    record_order_lines(&mut tx, order_id, &lines)?;
+    record_order_tenders(
+        &mut tx,
+        order_id,
+        data.customer_id,
+        Some(cashier_id),
+        &data.tenders,
+        &gift_card_debits,
+    )?;
+
+    if let Some(customer_id) = data.customer_id {
+        tiers::recompute_tier(&mut tx, customer_id)
+            .map_err(|e| CafeHubError::database("Failed to update membership tier", e))?;
+    }
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    println!(
+        "Counter order ID {} of {} paid by {} (cashier ID {})",
+        order_id, total_amount, payment_method, cashier_id
+    );
+
+    Ok(WalkInCheckoutResult {
+        order_id,
+        customer_id: data.customer_id,
+        item_count: lines.iter().map(|line| line.quantity).sum(),
+        subtotal,
+        tax_amount,
+        total_amount,
+        payment_method: payment_method.to_string(),
+        tenders: data.tenders,
+        cash_tendered,
+        change_due,
+        balance_after: balances.map(|(_, after)| after),
+    })
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// Callers may read their own records with `ManageOwnAccount`; anyone else's
+/// need `permission`.
+fn authorize_own_or(
+    sessions: &SessionStore,
+    token: &str,
+    owner_id: i64,
+    permission: Permission,
+) -> Result<(), CafeHubError> {
+    let session = sessions.authenticate(token)?;
+    if session.user_id == owner_id && session.permissions.contains(&Permission::ManageOwnAccount) {
+        return Ok(());
+    }
+    sessions.authorize(token, permission)?;
+    Ok(())
+}
+
+/// Reads column `index` of a row too wide for a tuple (more than 12 columns).
+fn take_column<T: mysql::prelude::FromValue>(
+    row: &mut mysql::Row,
+    index: usize,
+) -> Result<T, CafeHubError> {
+    row.take_opt(index)
+        .ok_or_else(|| CafeHubError::internal(format!("Missing column {}", index)))?
+        .map_err(|e| CafeHubError::internal(format!("Bad value in column {}: {}", index, e)))
+}
+
+/// Normalizes 1-based paging input into `(page, page_size)`.
+fn page_bounds(page: Option<u32>, page_size: Option<u32>) -> (u32, u32) {
+    (
+        page.unwrap_or(1).max(1),
+        page_size
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE),
+    )
+}
+
+/// Adds an inclusive date filter on the DATETIME `column`.
+fn push_date_range(
+    column: &str,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    conditions: &mut Vec<String>,
+    query_params: &mut Vec<(String, mysql::Value)>,
+) -> Result<(), CafeHubError> {
+    if let (Some(start_date), Some(end_date)) = (start_date, end_date) {
+        if start_date > end_date {
+            return Err(CafeHubError::InvalidDateRange {
+                start_date,
+                end_date,
+            });
+        }
+    }
+    if let Some(start_date) = start_date {
+        conditions.push(format!("{} >= :start_date", column));
+        query_params.push(("start_date".to_string(), start_date.into()));
+    }
+    if let Some(end_date) = end_date {
+        // end_date is inclusive, so compare against the start of the next day
+        let end_before = end_date
+            .succ_opt()
+            .ok_or_else(|| CafeHubError::internal("End date is out of range"))?;
+        conditions.push(format!("{} < :end_before", column));
+        query_params.push(("end_before".to_string(), end_before.into()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_user_orders(
+    token: String,
+    data: OrderQuery,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<OrderPage, CafeHubError> {
+    let session = sessions.authenticate(&token)?;
+    let user_id = data.user_id.unwrap_or(session.user_id);
+    authorize_own_or(&sessions, &token, user_id, Permission::ViewAllOrders)?;
+
+    let (page, page_size) = page_bounds(data.page, data.page_size);
+
+    let mut conditions: Vec<String> = vec!["o.user_id = :user_id".to_string()];
+    let mut query_params: Vec<(String, mysql::Value)> =
+        vec![("user_id".to_string(), user_id.into())];
+    push_date_range(
+        "o.created_at",
+        data.start_date,
+        data.end_date,
+        &mut conditions,
+        &mut query_params,
+    )?;
+
+    let where_clause = conditions.join(" AND ");
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let total: Option<i64> = conn
+        .exec_first(
+            format!("SELECT COUNT(*) FROM orders o WHERE {}", where_clause),
+            mysql::Params::from(query_params.clone()),
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for order count", e))?;
+
+    query_params.push(("limit".to_string(), page_size.into()));
+    query_params.push((
+        "offset".to_string(),
+        (u64::from(page - 1) * u64::from(page_size)).into(),
+    ));
+
+    let query = format!(
+        "SELECT o.id, o.user_id, CAST(COALESCE(SUM(oi.quantity), 0) AS SIGNED) AS item_count,
+                o.subtotal, o.discount_amount, o.total_amount, o.refunded_amount, o.status,
+                o.created_at
+         FROM orders o
+         LEFT JOIN order_items oi ON oi.order_id = o.id
+         WHERE {}
+         GROUP BY o.id
+         ORDER BY o.created_at DESC, o.id DESC
+         LIMIT :limit OFFSET :offset",
+        where_clause
+    );
+
+    let orders: Vec<OrderSummary> = conn
+        .exec_map(
+            query,
+            mysql::Params::from(query_params),
+            |(
+                id,
+                user_id,
+                item_count,
+                subtotal,
+                discount_amount,
+                total_amount,
+                refunded_amount,
+                status,
+                created_at,
+            )| OrderSummary {
+                id,
+                user_id,
+                item_count,
+                subtotal,
+                discount_amount,
+                total_amount,
+                refunded_amount,
+                status,
+                created_at,
+            },
+        )
+        .map_err(|e| {
+            eprintln!(
+                "Database query failed for orders of user ID {}: {}",
+                user_id, e
+            );
+            CafeHubError::database("Database query failed for orders", e)
+        })?;
+
+    Ok(OrderPage {
+        orders,
+        total: total.unwrap_or(0),
+        page,
+        page_size,
+    })
+}
+
+#[tauri::command]
+pub fn get_order_detail(
+    token: String,
+    order_id: i64,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<OrderDetail, CafeHubError> {
+    // Callers without view_all_orders only ever see their own orders; anyone
+    // else's is reported as not found, the same as a missing one.
+    let session = sessions.authenticate(&token)?;
+    let viewer_id = if session.permissions.contains(&Permission::ViewAllOrders) {
+        None
+    } else if session.permissions.contains(&Permission::ManageOwnAccount) {
+        Some(session.user_id)
+    } else {
+        sessions.authorize(&token, Permission::ViewAllOrders)?;
+        None
+    };
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut row: mysql::Row = conn
+        .exec_first(
+            "SELECT o.id, o.user_id, a.username, o.subtotal, o.discount_amount, cp.code, o.total_amount,
+                    o.refunded_amount, o.status, o.balance_before, o.balance_after, o.created_at,
+                    o.payment_method, o.cash_tendered, o.change_due, o.cashier_id, c.username,
+                    o.tax_amount, o.prices_include_tax
+             FROM orders o
+             LEFT JOIN account a ON o.user_id = a.id
+             LEFT JOIN coupons cp ON o.coupon_id = cp.id
+             LEFT JOIN account c ON o.cashier_id = c.id
+             WHERE o.id = :order_id AND (:viewer_id IS NULL OR o.user_id = :viewer_id)",
+            params! {
+                "order_id" => order_id,
+                "viewer_id" => viewer_id,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for order", e))?
+        .ok_or_else(|| CafeHubError::not_found(Entity::Order, order_id))?;
+
+    let mut order = OrderDetail {
+        id: take_column(&mut row, 0)?,
+        user_id: take_column(&mut row, 1)?,
+        username: take_column(&mut row, 2)?,
+        items: Vec::new(),
+        subtotal: take_column(&mut row, 3)?,
+        discount_amount: take_column(&mut row, 4)?,
+        coupon_code: take_column(&mut row, 5)?,
+        tax_amount: take_column(&mut row, 17)?,
+        prices_include_tax: take_column(&mut row, 18)?,
+        total_amount: take_column(&mut row, 6)?,
+        refunded_amount: take_column(&mut row, 7)?,
+        status: take_column(&mut row, 8)?,
+        payment: OrderPayment {
+            method: take_column(&mut row, 12)?,
+            tenders: Vec::new(),
+            cash_tendered: take_column(&mut row, 13)?,
+            change_due: take_column(&mut row, 14)?,
+            cashier_id: take_column(&mut row, 15)?,
+            cashier_name: take_column(&mut row, 16)?,
+        },
+        balance_before: take_column(&mut row, 9)?,
+        balance_after: take_column(&mut row, 10)?,
+        created_at: take_column(&mut row, 11)?,
+    };
+
+    order.payment.tenders = conn
+        .exec_map(
+            "SELECT tender_type, amount FROM order_tenders WHERE order_id = :order_id ORDER BY id ASC",
+            params! { "order_id" => order_id },
+            |(tender_type, amount)| OrderTender {
+                tender_type,
+                amount,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for order tenders", e))?;
+
+    order.items = conn
+        .exec_map(
+            "SELECT oi.id, oi.goods_id, g.goods_name, v.variant_name, oi.quantity, oi.refunded_quantity,
+                    oi.unit_price, oi.discount_amount, oi.tax_rate, oi.tax_amount, oi.line_total,
+                    oi.refunded_amount
+             FROM order_items oi
+             JOIN goods g ON oi.goods_id = g.id
+             LEFT JOIN goods_variant v ON oi.variant_id = v.id
+             WHERE oi.order_id = :order_id
+             ORDER BY oi.id ASC",
+            params! { "order_id" => order_id },
+            |(
+                id,
+                goods_id,
+                goods_name,
+                variant_name,
+                quantity,
+                refunded_quantity,
+                unit_price,
+                discount_amount,
+                tax_rate,
+                tax_amount,
+                line_total,
+                refunded_amount,
+            )| OrderItemDetail {
+                id,
+                goods_id,
+                goods_name,
+                variant_name,
+                quantity,
+                refunded_quantity,
+                unit_price,
+                discount_amount,
+                tax_rate,
+                tax_amount,
+                line_total,
+                refunded_amount,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for order items", e))?;
+
+    Ok(order)
+}
+
+/// Credits a refund back to the customer's balance and records it in the
+/// ledger. Returns the new balance.
+fn credit_refund(
+    tx: &mut mysql::Transaction,
+    customer_id: i64,
+    refund_amount: Decimal,
+    staff_id: i64,
+    order_id: i64,
+    refund_id: i64,
+    reason: &str,
+) -> Result<Decimal, CafeHubError> {
+    let current_balance: Option<Decimal> = tx
+        .exec_first(
+            "SELECT balance FROM account WHERE id = :user_id FOR UPDATE",
+            params! { "user_id" => customer_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query user", e))?
+        .ok_or_else(|| CafeHubError::not_found(Entity::Customer, customer_id))?;
+    let balance_after = current_balance.unwrap_or(Decimal::ZERO) + refund_amount;
+
+    tx.exec_drop(
+        "UPDATE account SET balance = :balance WHERE id = :user_id",
+        params! {
+            "balance" => balance_after,
+            "user_id" => customer_id,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to credit user balance", e))?;
+
+    ledger::record(
+        tx,
+        &LedgerEntry {
+            user_id: customer_id,
+            txn_type: TransactionType::Refund,
+            amount: refund_amount,
+            balance_after,
+            operator_id: Some(staff_id),
+            order_id: Some(order_id),
+            refund_id: Some(refund_id),
+            recharge_id: None,
+            gift_card_id: None,
+            note: Some(reason),
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record balance transaction", e))?;
+
+    Ok(balance_after)
+}
+
+const ORDER_STATUS_PARTIALLY_REFUNDED: i8 = 1;
+const ORDER_STATUS_REFUNDED: i8 = 2;
+
+#[tauri::command]
+pub fn refund_order(
+    token: String,
+    data: RefundOrderData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<RefundResult, CafeHubError> {
+    let staff_id = sessions
+        .authorize(&token, Permission::RefundOrders)?
+        .user_id;
+
+    let reason = data.reason.trim();
+    if reason.is_empty() {
+        return Err(CafeHubError::EmptyField { field: "reason" });
+    }
+    if reason.chars().count() > 255 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "reason",
+            max_length: 255,
+        });
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    // Walk-in orders have no customer; their refunds are paid out at the till.
+    let (customer_id, order_total, order_refunded, points_earned, points_redeemed): (
+        Option<i64>,
+        Decimal,
+        Decimal,
+        i32,
+        i32,
+    ) = tx
+        .exec_first(
+            "SELECT user_id, total_amount, refunded_amount, points_earned, points_redeemed
+             FROM orders WHERE id = :order_id FOR UPDATE",
+            params! { "order_id" => data.order_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query order", e))?
+        .ok_or_else(|| CafeHubError::not_found(Entity::Order, data.order_id))?;
+
+    struct OrderLine {
+        id: i64,
+        goods_id: i32,
+        refundable_quantity: i32,
+        refundable_amount: Decimal,
+        line_total: Decimal,
+        quantity: i32,
+    }
+    let lines: Vec<OrderLine> = tx
+        .exec_map(
+            "SELECT id, goods_id, quantity, refunded_quantity, line_total, refunded_amount
+             FROM order_items WHERE order_id = :order_id FOR UPDATE",
+            params! { "order_id" => data.order_id },
+            |(id, goods_id, quantity, refunded_quantity, line_total, refunded_amount): (
+                i64,
+                i32,
+                i32,
+                i32,
+                Decimal,
+                Decimal,
+            )| OrderLine {
+                id,
+                goods_id,
+                refundable_quantity: quantity - refunded_quantity,
+                refundable_amount: line_total - refunded_amount,
+                line_total,
+                quantity,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query order items", e))?;
+
+    // (order_item_id, goods_id, quantity, amount) for each line being refunded
+    let mut refund_lines: Vec<(i64, i32, i32, Decimal)> = Vec::new();
+    match &data.items {
+        None => {
+            for line in lines.iter().filter(|l| l.refundable_quantity > 0) {
+                refund_lines.push((
+                    line.id,
+                    line.goods_id,
+                    line.refundable_quantity,
+                    line.refundable_amount,
+                ));
+            }
+        }
+        Some(items) => {
+            for item in items {
+                if item.quantity <= 0 {
+                    return Err(CafeHubError::NonPositiveAmount { field: "quantity" });
+                }
+                let line = lines
+                    .iter()
+                    .find(|l| l.id == item.order_item_id)
+                    .ok_or_else(|| {
+                        CafeHubError::not_found(Entity::OrderItem, item.order_item_id)
+                    })?;
+                let already_requested: i32 = refund_lines
+                    .iter()
+                    .filter(|(id, ..)| *id == line.id)
+                    .map(|(_, _, quantity, _)| quantity)
+                    .sum();
+                if already_requested + item.quantity > line.refundable_quantity {
+                    return Err(CafeHubError::RefundQuantityExceeded {
+                        order_item_id: line.id,
+                        requested: already_requested + item.quantity,
+                        refundable: line.refundable_quantity,
+                    });
+                }
+                // The last units of a line take whatever is left so rounding never
+                // leaves a few cents unrefunded.
+                let amount = if already_requested + item.quantity == line.refundable_quantity {
+                    line.refundable_amount
+                        - refund_lines
+                            .iter()
+                            .filter(|(id, ..)| *id == line.id)
+                            .map(|(_, _, _, amount)| *amount)
+                            .sum::<Decimal>()
+                } else {
+                    (line.line_total / Decimal::from(line.quantity) * Decimal::from(item.quantity))
+                        .round_dp(2)
+                };
+                refund_lines.push((line.id, line.goods_id, item.quantity, amount));
+            }
+        }
+    }
+
+    if refund_lines.is_empty() {
+        return Err(CafeHubError::NothingToRefund {
+            order_id: data.order_id,
+        });
+    }
+
+    let refund_amount: Decimal = refund_lines.iter().map(|(_, _, _, amount)| *amount).sum();
+
+    for (order_item_id, goods_id, quantity, amount) in &refund_lines {
+        tx.exec_drop(
+            "UPDATE order_items SET refunded_quantity = refunded_quantity + :quantity, refunded_amount = refunded_amount + :amount WHERE id = :order_item_id",
+            params! {
+                "quantity" => quantity,
+                "amount" => amount,
+                "order_item_id" => order_item_id,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to update order item", e))?;
+
+        // Goes back wherever the variant keeps its stock today.
+        let stock_variant_id: Option<i32> = tx
+            .exec_first(
+                "SELECT v.id FROM order_items oi
+                 JOIN goods_variant v ON oi.variant_id = v.id
+                 WHERE oi.id = :order_item_id AND v.stock IS NOT NULL",
+                params! { "order_item_id" => order_item_id },
+            )
+            .map_err(|e| CafeHubError::database("Failed to query order item variant", e))?;
+        adjust_stock(&mut tx, *goods_id, stock_variant_id, *quantity)?;
+    }
+
+    let refund_quantity: i32 = refund_lines
+        .iter()
+        .map(|(_, _, quantity, _)| quantity)
+        .sum();
+    let remaining_quantity: i32 =
+        lines.iter().map(|l| l.refundable_quantity).sum::<i32>() - refund_quantity;
+
+    // Points follow the refunded share of the order: by amount, or by
+    // quantity for orders that came to nothing.
+    let (refunded_before, refunded_after, order_size) = if order_total > Decimal::ZERO {
+        (order_refunded, order_refunded + refund_amount, order_total)
+    } else {
+        let total_quantity: i32 = lines.iter().map(|l| l.quantity).sum();
+        let refunded_quantity =
+            total_quantity - lines.iter().map(|l| l.refundable_quantity).sum::<i32>();
+        (
+            Decimal::from(refunded_quantity),
+            Decimal::from(refunded_quantity + refund_quantity),
+            Decimal::from(total_quantity),
+        )
+    };
+    let points_share = |points: i32| {
+        loyalty::refunded_share(points, refunded_after, order_size)
+            - loyalty::refunded_share(points, refunded_before, order_size)
+    };
+    let (points_returned, points_reversed) = match customer_id {
+        Some(_) => (points_share(points_redeemed), points_share(points_earned)),
+        None => (0, 0),
+    };
+    let order_status = if remaining_quantity == 0 {
+        ORDER_STATUS_REFUNDED
+    } else {
+        ORDER_STATUS_PARTIALLY_REFUNDED
+    };
+
+    tx.exec_drop(
+        "UPDATE orders SET refunded_amount = refunded_amount + :amount, status = :status WHERE id = :order_id",
+        params! {
+            "amount" => refund_amount,
+            "status" => order_status,
+            "order_id" => data.order_id,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to update order", e))?;
+
+    // Refunds go back to the balance until everything paid from it has been
+    // returned; anything beyond that is paid out at the till.
+    let (balance_paid, balance_refunded): (Decimal, Decimal) = tx
+        .exec_first(
+            "SELECT
+                (SELECT COALESCE(SUM(amount), 0) FROM order_tenders WHERE order_id = :order_id AND tender_type = 'balance'),
+                (SELECT COALESCE(SUM(balance_amount), 0) FROM refunds WHERE order_id = :order_id)",
+            params! { "order_id" => data.order_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query order tenders", e))?
+        .unwrap_or((Decimal::ZERO, Decimal::ZERO));
+    let balance_refund = match customer_id {
+        Some(_) => refund_amount
+            .min(balance_paid - balance_refunded)
+            .max(Decimal::ZERO),
+        None => Decimal::ZERO,
+    };
+
+    // Then back onto the gift cards that paid for the order, in the order
+    // they were tendered.
+    let gift_card_tenders: Vec<(i64, Decimal, Decimal)> = tx
+        .exec(
+            "SELECT t.gift_card_id, SUM(t.amount),
+                    (SELECT COALESCE(SUM(g.amount), 0) FROM gift_card_transactions g
+                     WHERE g.order_id = :order_id AND g.gift_card_id = t.gift_card_id AND g.txn_type = 'refund')
+             FROM order_tenders t
+             WHERE t.order_id = :order_id AND t.gift_card_id IS NOT NULL
+             GROUP BY t.gift_card_id
+             ORDER BY MIN(t.id)",
+            params! { "order_id" => data.order_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query order tenders", e))?;
+    let mut unallocated = refund_amount - balance_refund;
+    let mut gift_card_refunds: Vec<(i64, Decimal)> = Vec::new();
+    for (gift_card_id, paid, refunded) in gift_card_tenders {
+        let share = unallocated.min(paid - refunded);
+        if share > Decimal::ZERO {
+            gift_card_refunds.push((gift_card_id, share));
+            unallocated -= share;
+        }
+    }
+    let gift_card_refund: Decimal = gift_card_refunds.iter().map(|(_, amount)| *amount).sum();
+
+    tx.exec_drop(
+        "INSERT INTO refunds (order_id, amount, balance_amount, gift_card_amount, reason, refunded_by, created_at)
+         VALUES (:order_id, :amount, :balance_amount, :gift_card_amount, :reason, :refunded_by, :created_at)",
+        params! {
+            "order_id" => data.order_id,
+            "amount" => refund_amount,
+            "balance_amount" => balance_refund,
+            "gift_card_amount" => gift_card_refund,
+            "reason" => reason,
+            "refunded_by" => staff_id,
+            "created_at" => Local::now().naive_local(),
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record refund", e))?;
+
+    let refund_id =
+        tx.last_insert_id()
+            .ok_or_else(|| CafeHubError::internal("Failed to read new refund ID"))? as i64;
+
+    tx.exec_batch(
+        "INSERT INTO refund_items (refund_id, order_item_id, quantity, amount) VALUES (:refund_id, :order_item_id, :quantity, :amount)
+         ON DUPLICATE KEY UPDATE quantity = quantity + VALUES(quantity), amount = amount + VALUES(amount)",
+        refund_lines.iter().map(|(order_item_id, _, quantity, amount)| {
+            params! {
+                "refund_id" => refund_id,
+                "order_item_id" => order_item_id,
+                "quantity" => quantity,
+                "amount" => amount,
+            }
+        }),
+    )
+    .map_err(|e| CafeHubError::database("Failed to record refund items", e))?;
+
+    let balance_after = match customer_id {
+        Some(customer_id) if balance_refund > Decimal::ZERO => Some(credit_refund(
+            &mut tx,
+            customer_id,
+            balance_refund,
+            staff_id,
+            data.order_id,
+            refund_id,
+            reason,
+        )?),
+        _ => None,
+    };
+
+    for (gift_card_id, amount) in gift_card_refunds {
+        let balance: Decimal = tx
+            .exec_first(
+                "SELECT balance FROM gift_cards WHERE id = :gift_card_id FOR UPDATE",
+                params! { "gift_card_id" => gift_card_id },
+            )
+            .map_err(|e| CafeHubError::database("Failed to query gift card", e))?
+            .ok_or_else(|| CafeHubError::internal("Gift card for order tender is missing"))?;
+        gift_cards::record(
+            &mut tx,
+            &gift_cards::GiftCardEntry {
+                gift_card_id,
+                txn_type: GiftCardTxnType::Refund,
+                amount,
+                balance_after: balance + amount,
+                payment_method: None,
+                order_id: Some(data.order_id),
+                refund_id: Some(refund_id),
+                user_id: customer_id,
+                operator_id: Some(staff_id),
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to record gift card transaction", e))?;
+    }
+
+    let points_after = match customer_id {
+        Some(customer_id) if points_returned > 0 || points_reversed > 0 => Some(refund_points(
+            &mut tx,
+            customer_id,
+            points_returned,
+            points_reversed,
+            staff_id,
+            data.order_id,
+            reason,
+        )?),
+        _ => None,
+    };
+
+    if let Some(customer_id) = customer_id {
+        tiers::recompute_tier(&mut tx, customer_id)
+            .map_err(|e| CafeHubError::database("Failed to update membership tier", e))?;
+    }
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    println!(
+        "Order ID {} refunded {} by staff ID {}: {}",
+        data.order_id, refund_amount, staff_id, reason
+    );
+
+    Ok(RefundResult {
+        refund_id,
+        order_id: data.order_id,
+        refund_amount,
+        balance_refund,
+        gift_card_refund,
+        order_status,
+        balance_after,
+        points_returned,
+        points_reversed,
+        points_after,
+    })
+}
+
+/// Gives back points redeemed on the refunded part of an order and takes
+/// back those it earned. Points already spent can leave the account below
+/// zero until it earns them back. Returns the account's points afterwards.
+fn refund_points(
+    tx: &mut mysql::Transaction,
+    customer_id: i64,
+    points_returned: i32,
+    points_reversed: i32,
+    staff_id: i64,
+    order_id: i64,
+    reason: &str,
+) -> Result<i32, CafeHubError> {
+    tx.exec_first::<i64, _, _>(
+        "SELECT id FROM account WHERE id = :user_id FOR UPDATE",
+        params! { "user_id" => customer_id },
+    )
+    .map_err(|e| CafeHubError::database("Failed to query user", e))?
+    .ok_or_else(|| CafeHubError::not_found(Entity::Customer, customer_id))?;
+
+    let mut points = loyalty::expire_points(tx, customer_id)
+        .map_err(|e| CafeHubError::database("Failed to expire points", e))?;
+    for change in [points_returned, -points_reversed] {
+        if change == 0 {
+            continue;
+        }
+        points += change;
+        loyalty::record(
+            tx,
+            &loyalty::PointsEntry {
+                user_id: customer_id,
+                txn_type: PointsTxnType::Refund,
+                points: change,
+                points_after: points,
+                operator_id: Some(staff_id),
+                order_id: Some(order_id),
+                note: Some(reason),
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to record points transaction", e))?;
+    }
+
+    tx.exec_drop(
+        "UPDATE account SET points = :points WHERE id = :user_id",
+        params! {
+            "points" => points,
+            "user_id" => customer_id,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to update user points", e))?;
+
+    Ok(points)
+}
+
+#[tauri::command]
+pub fn get_balance_history(
+    token: String,
+    data: BalanceHistoryQuery,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<BalanceHistoryPage, CafeHubError> {
+    let session = sessions.authenticate(&token)?;
+    let user_id = data.user_id.unwrap_or(session.user_id);
+    authorize_own_or(&sessions, &token, user_id, Permission::RechargeBalance)?;
+
+    let (page, page_size) = page_bounds(data.page, data.page_size);
+
+    let mut conditions: Vec<String> = vec!["t.user_id = :user_id".to_string()];
+    let mut query_params: Vec<(String, mysql::Value)> =
+        vec![("user_id".to_string(), user_id.into())];
+    push_date_range(
+        "t.created_at",
+        data.start_date,
+        data.end_date,
+        &mut conditions,
+        &mut query_params,
+    )?;
+    let where_clause = conditions.join(" AND ");
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let balances: Option<(Decimal, Decimal)> = conn
+        .exec_first(
+            "SELECT COALESCE(a.balance, 0),
+                    (SELECT COALESCE(SUM(amount), 0) FROM balance_transactions WHERE user_id = a.id)
+             FROM account a WHERE a.id = :user_id",
+            params! { "user_id" => user_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query user balance", e))?;
+    let (current_balance, ledger_balance) =
+        balances.ok_or_else(|| CafeHubError::not_found(Entity::User, user_id))?;
+
+    let total: Option<i64> = conn
+        .exec_first(
+            format!(
+                "SELECT COUNT(*) FROM balance_transactions t WHERE {}",
+                where_clause
+            ),
+            mysql::Params::from(query_params.clone()),
+        )
+        .map_err(|e| {
+            CafeHubError::database("Database query failed for balance transaction count", e)
+        })?;
+
+    query_params.push(("limit".to_string(), page_size.into()));
+    query_params.push((
+        "offset".to_string(),
+        (u64::from(page - 1) * u64::from(page_size)).into(),
+    ));
+
+    let query = format!(
+        "SELECT t.id, t.txn_type, t.amount, t.balance_after, t.operator_id, op.username,
+                t.order_id, t.refund_id, t.recharge_id, t.gift_card_id, t.note, t.created_at
+         FROM balance_transactions t
+         LEFT JOIN account op ON t.operator_id = op.id
+         WHERE {}
+         ORDER BY t.created_at DESC, t.id DESC
+         LIMIT :limit OFFSET :offset",
+        where_clause
+    );
+
+    let transactions: Vec<BalanceTransaction> = conn
+        .exec_map(
+            query,
+            mysql::Params::from(query_params),
+            |(
+                id,
+                txn_type,
+                amount,
+                balance_after,
+                operator_id,
+                operator_name,
+                order_id,
+                refund_id,
+                recharge_id,
+                gift_card_id,
+                note,
+                created_at,
+            )| BalanceTransaction {
+                id,
+                txn_type,
+                amount,
+                balance_after,
+                operator_id,
+                operator_name,
+                order_id,
+                refund_id,
+                recharge_id,
+                gift_card_id,
+                note,
+                created_at,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for balance history", e))?;
+
+    Ok(BalanceHistoryPage {
+        user_id,
+        current_balance,
+        ledger_balance,
+        transactions,
+        total: total.unwrap_or(0),
+        page,
+        page_size,
+    })
+}
+
+/// Lists every account whose stored balance differs from the sum of its
+/// ledger entries. An empty result means the ledger is consistent.
+#[tauri::command]
+pub fn check_balance_consistency(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<BalanceDiscrepancy>, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewSystemStatus)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let query = "
+        SELECT a.id, a.username, COALESCE(a.balance, 0) AS stored_balance, COALESCE(l.total, 0) AS ledger_balance
+        FROM account a
+        LEFT JOIN (
+            SELECT user_id, SUM(amount) AS total FROM balance_transactions GROUP BY user_id
+        ) l ON l.user_id = a.id
+        WHERE COALESCE(a.balance, 0) <> COALESCE(l.total, 0)
+        ORDER BY a.id ASC";
+
+    let results: Vec<BalanceDiscrepancy> = conn
+        .query_map(
+            query,
+            |(user_id, username, stored_balance, ledger_balance): (
+                i64,
+                String,
+                Decimal,
+                Decimal,
+            )| {
+                BalanceDiscrepancy {
+                    user_id,
+                    username,
+                    stored_balance,
+                    ledger_balance,
+                    difference: stored_balance - ledger_balance,
+                }
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for balance consistency", e))?;
+
+    if !results.is_empty() {
+        eprintln!(
+            "Balance ledger is inconsistent for {} account(s)",
+            results.len()
+        );
+    }
+
+    Ok(results)
+}
+
+#[tauri::command]
+pub fn adjust_points(
+    token: String,
+    data: PointsAdjustmentData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<PointsAdjustmentResult, CafeHubError> {
+    let staff_id = sessions
+        .authorize(&token, Permission::AdjustPoints)?
+        .user_id;
+
+    if data.points == 0 {
+        return Err(CafeHubError::ZeroAdjustment);
+    }
+    let note = data.note.trim();
+    if note.is_empty() {
+        return Err(CafeHubError::EmptyField { field: "note" });
+    }
+    if note.chars().count() > 255 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "note",
+            max_length: 255,
+        });
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let user_type: Option<i8> = tx
+        .exec_first(
+            "SELECT user_type FROM account WHERE id = :user_id FOR UPDATE",
+            params! { "user_id" => data.user_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query user", e))?;
+    match user_type {
+        Some(1) => {}
+        Some(_) => {
+            return Err(CafeHubError::NotACustomer {
+                user_id: data.user_id,
+            })
+        }
+        None => return Err(CafeHubError::not_found(Entity::User, data.user_id)),
+    }
+
+    let points_before = loyalty::expire_points(&mut tx, data.user_id)
+        .map_err(|e| CafeHubError::database("Failed to expire points", e))?;
+    let points_after = points_before + data.points;
+    if points_after < 0 {
+        return Err(CafeHubError::InsufficientPoints {
+            required: -data.points,
+            available: points_before,
+        });
+    }
+
+    tx.exec_drop(
+        "UPDATE account SET points = :points WHERE id = :user_id",
+        params! {
+            "points" => points_after,
+            "user_id" => data.user_id,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to update user points", e))?;
+
+    loyalty::record(
+        &mut tx,
+        &loyalty::PointsEntry {
+            user_id: data.user_id,
+            txn_type: PointsTxnType::Adjust,
+            points: data.points,
+            points_after,
+            operator_id: Some(staff_id),
+            order_id: None,
+            note: Some(note),
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record points transaction", e))?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    println!(
+        "Staff ID {} adjusted points for user ID {} by {}: {}",
+        staff_id, data.user_id, data.points, note
+    );
+
+    Ok(PointsAdjustmentResult {
+        user_id: data.user_id,
+        points: data.points,
+        points_after,
+    })
+}
+
+#[tauri::command]
+pub fn get_points_history(
+    token: String,
+    data: PointsHistoryQuery,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<PointsHistoryPage, CafeHubError> {
+    let session = sessions.authenticate(&token)?;
+    let user_id = data.user_id.unwrap_or(session.user_id);
+    authorize_own_or(&sessions, &token, user_id, Permission::ViewAllUsers)?;
+
+    let (page, page_size) = page_bounds(data.page, data.page_size);
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let points = refresh_points(&mut conn, user_id)?
+        .ok_or_else(|| CafeHubError::not_found(Entity::User, user_id))?;
+
+    let total: Option<i64> = conn
+        .exec_first(
+            "SELECT COUNT(*) FROM points_transactions WHERE user_id = :user_id",
+            params! { "user_id" => user_id },
+        )
+        .map_err(|e| {
+            CafeHubError::database("Database query failed for points transaction count", e)
+        })?;
+
+    let transactions: Vec<PointsTransaction> = conn
+        .exec_map(
+            "SELECT t.id, t.txn_type, t.points, t.points_after, t.expires_at, t.order_id,
+                    t.operator_id, op.username, t.note, t.created_at
+             FROM points_transactions t
+             LEFT JOIN account op ON t.operator_id = op.id
+             WHERE t.user_id = :user_id
+             ORDER BY t.created_at DESC, t.id DESC
+             LIMIT :limit OFFSET :offset",
+            params! {
+                "user_id" => user_id,
+                "limit" => page_size,
+                "offset" => u64::from(page - 1) * u64::from(page_size),
+            },
+            |(
+                id,
+                txn_type,
+                points,
+                points_after,
+                expires_at,
+                order_id,
+                operator_id,
+                operator_name,
+                note,
+                created_at,
+            )| PointsTransaction {
+                id,
+                txn_type,
+                points,
+                points_after,
+                expires_at,
+                order_id,
+                operator_id,
+                operator_name,
+                note,
+                created_at,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Database query failed for points history", e))?;
+
+    Ok(PointsHistoryPage {
+        user_id,
+        points,
+        transactions,
+        total: total.unwrap_or(0),
+        page,
+        page_size,
+    })
+}
+
+#[tauri::command]
+pub fn get_membership_tiers(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<MembershipTier>, CafeHubError> {
+    sessions.authenticate(&token)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    conn.query_map(
+        "SELECT t.id, t.name, t.min_spend, t.discount_percent,
+                (SELECT COUNT(*) FROM account a WHERE a.tier_id = t.id)
+         FROM membership_tiers t ORDER BY t.min_spend ASC, t.id ASC",
+        |(id, name, min_spend, discount_percent, member_count)| MembershipTier {
+            id,
+            name,
+            min_spend,
+            discount_percent,
+            member_count,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Database query failed for membership tiers", e))
+}
+
+/// Changes a tier's threshold or discount. Members move on the next
+/// purchase or `recompute_membership_tiers`.
+#[tauri::command]
+pub fn update_membership_tier(
+    token: String,
+    tier_id: i32,
+    data: UpdateMembershipTierData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+
+    if data.min_spend < Decimal::ZERO {
+        return Err(CafeHubError::NegativeValue { field: "min_spend" });
+    }
+    if data.discount_percent < Decimal::ZERO || data.discount_percent > Decimal::ONE_HUNDRED {
+        return Err(CafeHubError::PercentageOutOfRange {
+            field: "discount_percent",
+        });
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let tier_exists: Option<i32> = conn
+        .exec_first(
+            "SELECT id FROM membership_tiers WHERE id = :tier_id",
+            params! { "tier_id" => tier_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query membership tier", e))?;
+    if tier_exists.is_none() {
+        return Err(CafeHubError::not_found(Entity::MembershipTier, tier_id));
+    }
+
+    conn.exec_drop(
+        "UPDATE membership_tiers SET min_spend = :min_spend, discount_percent = :discount_percent WHERE id = :tier_id",
+        params! {
+            "min_spend" => data.min_spend,
+            "discount_percent" => data.discount_percent,
+            "tier_id" => tier_id,
+        },
+    )
+    .map_err(|e| {
+        eprintln!("Database update failed for membership tier ID {}: {}", tier_id, e);
+        CafeHubError::database("Database error while updating membership tier", e)
+    })
+}
+
+/// Re-evaluates every customer's tier. Run it after changing thresholds, or
+/// periodically so old spend drops out of the window.
+#[tauri::command]
+pub fn recompute_membership_tiers(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<TierRecomputeResult, CafeHubError> {
+    sessions.authorize(&token, Permission::ManagePromotions)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let customers: Option<i64> = conn
+        .query_first("SELECT COUNT(*) FROM account WHERE user_type = 1")
+        .map_err(|e| CafeHubError::database("Failed to count customers", e))?;
+
+    tiers::recompute_all_tiers(&mut conn)
+        .map_err(|e| CafeHubError::database("Failed to recompute membership tiers", e))?;
+    let changed = conn.affected_rows();
+
+    println!("Recomputed membership tiers: {} changed", changed);
+
+    Ok(TierRecomputeResult {
+        customers: customers.unwrap_or(0),
+        changed,
+    })
+}
+
+fn load_gift_card_row<Q: Queryable>(
+    conn: &mut Q,
+    code: &str,
+) -> Result<Option<GiftCard>, CafeHubError> {
+    conn.exec_map(
+        "SELECT id, code, initial_value, balance, note, issued_by, issued_at FROM gift_cards WHERE code = :code",
+        params! { "code" => code },
+        |(id, code, initial_value, balance, note, issued_by, issued_at)| GiftCard {
+            id,
+            code,
+            initial_value,
+            balance,
+            note,
+            issued_by,
+            issued_at,
+        },
+    )
+    .map(|cards| cards.into_iter().next())
+    .map_err(|e| CafeHubError::database("Failed to query gift card", e))
+}
+
+/// Issues a gift card worth `initial_value`, paid for at the till.
+#[tauri::command]
+pub fn issue_gift_card(
+    token: String,
+    data: IssueGiftCardData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<GiftCard, CafeHubError> {
+    let staff_id = sessions
+        .authorize(&token, Permission::ManageGiftCards)?
+        .user_id;
+
+    if data.initial_value <= Decimal::ZERO {
+        return Err(CafeHubError::NonPositiveAmount {
+            field: "initial_value",
+        });
+    }
+    let code = data
+        .code
+        .as_deref()
+        .map(gift_cards::normalize_code)
+        .filter(|code| !code.is_empty());
+    if code.as_ref().is_some_and(|code| code.chars().count() > 32) {
+        return Err(CafeHubError::FieldTooLong {
+            field: "code",
+            max_length: 32,
+        });
+    }
+    let note = data
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.chars().count() > 255) {
+        return Err(CafeHubError::FieldTooLong {
+            field: "note",
+            max_length: 255,
+        });
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let issued_at = Local::now().naive_local();
+    // A generated code that happens to collide is simply drawn again.
+    let mut attempts = 0;
+    let code = loop {
+        let candidate = code.clone().unwrap_or_else(gift_cards::generate_code);
+        let result = tx.exec_drop(
+            "INSERT INTO gift_cards (code, initial_value, balance, note, issued_by, issued_at)
+             VALUES (:code, :initial_value, 0, :note, :issued_by, :issued_at)",
+            params! {
+                "code" => &candidate,
+                "initial_value" => data.initial_value,
+                "note" => note,
+                "issued_by" => staff_id,
+                "issued_at" => issued_at,
+            },
+        );
+        match result {
+            Ok(()) => break candidate,
+            Err(MySQLError::MySqlError(ref mysql_err)) if mysql_err.code == 1062 => {
+                attempts += 1;
+                if code.is_some() || attempts >= 3 {
+                    return Err(CafeHubError::NameTaken {
+                        entity: Entity::GiftCard,
+                        name: candidate,
+                    });
+                }
+            }
+            Err(e) => return Err(CafeHubError::database("Failed to issue gift card", e)),
+        }
+    };
+
+    let gift_card_id = tx
+        .last_insert_id()
+        .ok_or_else(|| CafeHubError::internal("Failed to read new gift card ID"))?
+        as i64;
+
+    gift_cards::record(
+        &mut tx,
+        &gift_cards::GiftCardEntry {
+            gift_card_id,
+            txn_type: GiftCardTxnType::Issue,
+            amount: data.initial_value,
+            balance_after: data.initial_value,
+            payment_method: Some(data.payment_method.as_str()),
+            order_id: None,
+            refund_id: None,
+            user_id: None,
+            operator_id: Some(staff_id),
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record gift card transaction", e))?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    println!(
+        "Staff ID {} issued gift card ID {} worth {} ({})",
+        staff_id,
+        gift_card_id,
+        data.initial_value,
+        data.payment_method.as_str()
+    );
+
+    Ok(GiftCard {
+        id: gift_card_id,
+        code,
+        initial_value: data.initial_value,
+        balance: data.initial_value,
+        note: note.map(str::to_string),
+        issued_by: staff_id,
+        issued_at,
+    })
+}
+
+/// Adds value to an existing gift card.
+#[tauri::command]
+pub fn load_gift_card(
+    token: String,
+    data: LoadGiftCardData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<GiftCard, CafeHubError> {
+    let staff_id = sessions
+        .authorize(&token, Permission::ManageGiftCards)?
+        .user_id;
+
+    if data.amount <= Decimal::ZERO {
+        return Err(CafeHubError::NonPositiveAmount { field: "amount" });
+    }
+    let code = gift_cards::normalize_code(&data.code);
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let (gift_card_id, balance): (i64, Decimal) = tx
+        .exec_first(
+            "SELECT id, balance FROM gift_cards WHERE code = :code FOR UPDATE",
+            params! { "code" => &code },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query gift card", e))?
+        .ok_or_else(|| CafeHubError::GiftCardNotFound { code: code.clone() })?;
+
+    gift_cards::record(
+        &mut tx,
+        &gift_cards::GiftCardEntry {
+            gift_card_id,
+            txn_type: GiftCardTxnType::Load,
+            amount: data.amount,
+            balance_after: balance + data.amount,
+            payment_method: Some(data.payment_method.as_str()),
+            order_id: None,
+            refund_id: None,
+            user_id: None,
+            operator_id: Some(staff_id),
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record gift card transaction", e))?;
+
+    let card = load_gift_card_row(&mut tx, &code)?
+        .ok_or_else(|| CafeHubError::GiftCardNotFound { code: code.clone() })?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    println!(
+        "Staff ID {} loaded {} onto gift card ID {} ({})",
+        staff_id,
+        data.amount,
+        gift_card_id,
+        data.payment_method.as_str()
+    );
+
+    Ok(card)
+}
+
+/// Looks up a card by its code. Holding the code is what entitles someone
+/// to the card, so any signed-in user may check a balance.
+#[tauri::command]
+pub fn get_gift_card(
+    token: String,
+    code: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<GiftCardDetail, CafeHubError> {
+    sessions.authenticate(&token)?;
+
+    let code = gift_cards::normalize_code(&code);
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let card =
+        load_gift_card_row(&mut conn, &code)?.ok_or(CafeHubError::GiftCardNotFound { code })?;
+
+    let transactions = conn
+        .exec_map(
+            "SELECT id, txn_type, amount, balance_after, payment_method, order_id, refund_id, user_id, operator_id, created_at
+             FROM gift_card_transactions WHERE gift_card_id = :gift_card_id
+             ORDER BY created_at DESC, id DESC",
+            params! { "gift_card_id" => card.id },
+            |(
+                id,
+                txn_type,
+                amount,
+                balance_after,
+                payment_method,
+                order_id,
+                refund_id,
+                user_id,
+                operator_id,
+                created_at,
+            )| GiftCardTransaction {
+                id,
+                txn_type,
+                amount,
+                balance_after,
+                payment_method,
+                order_id,
+                refund_id,
+                user_id,
+                operator_id,
+                created_at,
+            },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query gift card transactions", e))?;
+
+    Ok(GiftCardDetail { card, transactions })
+}
+
+/// Moves everything left on a gift card into a member's balance. Members
+/// may redeem onto their own account; staff can do it for anyone.
+#[tauri::command]
+pub fn transfer_gift_card_to_balance(
+    token: String,
+    data: GiftCardTransferData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<GiftCardTransferResult, CafeHubError> {
+    authorize_own_or(&sessions, &token, data.user_id, Permission::ManageGiftCards)?;
+    let operator_id = sessions.authenticate(&token)?.user_id;
+
+    let code = gift_cards::normalize_code(&data.code);
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
 
-        let item_total_price = price_per_item * Decimal::from(item.quantity);
-        total_purchase_price += item_total_price;
-        processed_item_details.push(ProcessedItemDetail {
-            goods_id: item.goods_id,
-            quantity: item.quantity,
-            item_total_price,
-        });
+    let (gift_card_id, amount): (i64, Decimal) = tx
+        .exec_first(
+            "SELECT id, balance FROM gift_cards WHERE code = :code FOR UPDATE",
+            params! { "code" => &code },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query gift card", e))?
+        .ok_or_else(|| CafeHubError::GiftCardNotFound { code: code.clone() })?;
+    if amount <= Decimal::ZERO {
+        return Err(CafeHubError::GiftCardEmpty { code });
     }
 
-    let user_info: Option<(Decimal, i8)> = tx
+    let account: Option<(i8, Option<Decimal>)> = tx
         .exec_first(
-            "SELECT balance, user_type FROM account WHERE id = :user_id AND user_type = 1 FOR UPDATE",
+            "SELECT user_type, balance FROM account WHERE id = :user_id FOR UPDATE",
             params! { "user_id" => data.user_id },
         )
-        .map_err(|e| format!("Failed to query user: {}", e))?;
-
-    let (current_balance, _user_type) = match user_info {
-        Some(info) => info,
-        None => {
-            return Err(format!(
-                "Customer account with ID {} not found.",
-                data.user_id
-            ));
+        .map_err(|e| CafeHubError::database("Failed to query user", e))?;
+    let balance_before = match account {
+        Some((1, balance)) => balance.unwrap_or(Decimal::ZERO),
+        Some(_) => {
+            return Err(CafeHubError::NotACustomer {
+                user_id: data.user_id,
+            })
         }
+        None => return Err(CafeHubError::not_found(Entity::User, data.user_id)),
     };
-
-    if current_balance < total_purchase_price {
-        return Ok(2);
-    }
-
-    for p_item_detail in &processed_item_details {
-        tx.exec_drop(
-            "UPDATE goods SET stock = stock - :quantity WHERE id = :goods_id",
-            params! {
-                "quantity" => p_item_detail.quantity,
-                "goods_id" => p_item_detail.goods_id,
-            },
-        )
-        .map_err(|e| {
-            format!(
-                "Failed to update stock for goods ID {}: {}",
-                p_item_detail.goods_id, e
-            )
-        })?;
-    }
+    let balance_after = balance_before + amount;
 
     tx.exec_drop(
-        "UPDATE account SET balance = balance - :total_price WHERE id = :user_id",
+        "UPDATE account SET balance = :balance WHERE id = :user_id",
         params! {
-            "total_price" => total_purchase_price,
+            "balance" => balance_after,
             "user_id" => data.user_id,
         },
     )
-    .map_err(|e| format!("Failed to update user balance: {}", e))?;
-
-    let current_month_str = Local::now().format("%Y-%m").to_string();
-    for p_item_detail in &processed_item_details {
-        tx.exec_drop(
-            "INSERT INTO consumption (user_id, month, goods_id, amount) VALUES (:user_id, :month, :goods_id, :amount)
-             ON DUPLICATE KEY UPDATE amount = amount + VALUES(amount)",
-            params! {
-                "user_id" => data.user_id,
-                "month" => &current_month_str,
-                "goods_id" => p_item_detail.goods_id,
-                "amount" => p_item_detail.item_total_price,
-            },
-        )
-        .map_err(|e| format!("Failed to record consumption for goods ID {}: {}", p_item_detail.goods_id, e))?;
-    }
+    .map_err(|e| CafeHubError::database("Failed to update user balance", e))?;
+
+    gift_cards::record(
+        &mut tx,
+        &gift_cards::GiftCardEntry {
+            gift_card_id,
+            txn_type: GiftCardTxnType::Transfer,
+            amount: -amount,
+            balance_after: Decimal::ZERO,
+            payment_method: None,
+            order_id: None,
+            refund_id: None,
+            user_id: Some(data.user_id),
+            operator_id: Some(operator_id),
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record gift card transaction", e))?;
+
+    ledger::record(
+        &mut tx,
+        &LedgerEntry {
+            user_id: data.user_id,
+            txn_type: TransactionType::GiftCardTransfer,
+            amount,
+            balance_after,
+            operator_id: Some(operator_id),
+            order_id: None,
+            refund_id: None,
+            recharge_id: None,
+            gift_card_id: Some(gift_card_id),
+            note: None,
+        },
+    )
+    .map_err(|e| CafeHubError::database("Failed to record balance transaction", e))?;
 
     tx.commit()
-        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
 
-    Ok(0)
+    println!(
+        "Gift card ID {} transferred {} to user ID {}",
+        gift_card_id, amount, data.user_id
+    );
+
+    Ok(GiftCardTransferResult {
+        gift_card_id,
+        user_id: data.user_id,
+        amount,
+        balance_after,
+    })
 }
 
 #[tauri::command]
-pub fn get_all_lost_items(mysql_pool: State<Pool>) -> Result<Vec<LostItem>, String> {
+pub fn get_all_lost_items(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<LostItem>, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewLostItems)?;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let query = "
         SELECT
@@ -853,23 +5267,29 @@ pub fn get_all_lost_items(mysql_pool: State<Pool>) -> Result<Vec<LostItem>, Stri
                 }
             },
         )
-        .map_err(|e| format!("Database query failed for all lost items: {}", e))?;
+        .map_err(|e| CafeHubError::database("Database query failed for all lost items", e))?;
 
     Ok(results)
 }
 
 #[tauri::command]
 pub fn report_lost_item(
+    token: String,
     data: ReportLostItemData,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<String, String> {
+) -> Result<String, CafeHubError> {
+    let pick_user_id = sessions
+        .authorize(&token, Permission::ReportLostItem)?
+        .user_id;
+
     if data.item_name.is_empty() {
-        return Err("Item name cannot be empty".to_string());
+        return Err(CafeHubError::EmptyField { field: "item_name" });
     }
 
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let current_date = Local::now().date_naive();
     let status: i8 = 0;
@@ -879,7 +5299,7 @@ pub fn report_lost_item(
         params! {
             "item_name" => &data.item_name,
             "pick_place" => &data.pick_place,
-            "pick_user_id" => &data.pick_user_id,
+            "pick_user_id" => pick_user_id,
             "pick_time" => current_date,
             "status" => status,
         }
@@ -895,16 +5315,28 @@ pub fn report_lost_item(
                 "Database insert failed for lost item {}: {}",
                 data.item_name, e
             );
-            Err(format!("Database error while reporting lost item: {}", e))
+            Err(CafeHubError::database(
+                "Database error while reporting lost item",
+                e,
+            ))
         }
     }
 }
 
 #[tauri::command]
-pub fn claim_lost_item(data: ClaimLostItemData, mysql_pool: State<Pool>) -> Result<String, String> {
+pub fn claim_lost_item(
+    token: String,
+    data: ClaimLostItemData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<String, CafeHubError> {
+    let claim_user_id = sessions
+        .authorize(&token, Permission::ClaimLostItem)?
+        .user_id;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let current_date = Local::now().date_naive();
     let new_status: i8 = 1;
@@ -914,7 +5346,7 @@ pub fn claim_lost_item(data: ClaimLostItemData, mysql_pool: State<Pool>) -> Resu
             "SELECT status FROM lost_items WHERE id = :item_id",
             params! { "item_id" => data.item_id },
         )
-        .map_err(|e| format!("Failed to query lost item status: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to query lost item status", e))?;
 
     match item_status {
         Some(0) => {
@@ -922,7 +5354,7 @@ pub fn claim_lost_item(data: ClaimLostItemData, mysql_pool: State<Pool>) -> Resu
                 "UPDATE lost_items SET status = :status, claim_user_id = :claim_user_id, claim_time = :claim_time WHERE id = :item_id",
                 params! {
                     "status" => new_status,
-                    "claim_user_id" => data.claim_user_id,
+                    "claim_user_id" => claim_user_id,
                     "claim_time" => current_date,
                     "item_id" => data.item_id,
                 }
@@ -933,7 +5365,9 @@ pub fn claim_lost_item(data: ClaimLostItemData, mysql_pool: State<Pool>) -> Resu
                     if conn.affected_rows() > 0 {
                         Ok(format!("Item ID {} claimed successfully.", data.item_id))
                     } else {
-                        Err(format!("Failed to update item ID {}. It might have been claimed or deleted concurrently.", data.item_id))
+                        Err(CafeHubError::AlreadyClaimed {
+                            item_id: data.item_id,
+                        })
                     }
                 }
                 Err(e) => {
@@ -941,44 +5375,60 @@ pub fn claim_lost_item(data: ClaimLostItemData, mysql_pool: State<Pool>) -> Resu
                         "Database update failed for claiming item ID {}: {}",
                         data.item_id, e
                     );
-                    Err(format!("Database error while claiming item: {}", e))
+                    Err(CafeHubError::database(
+                        "Database error while claiming item",
+                        e,
+                    ))
                 }
             }
         }
-        Some(1) => Err(format!(
-            "Item ID {} has already been claimed.",
-            data.item_id
-        )),
-        None => Err(format!("Lost item with ID {} not found.", data.item_id)),
-        Some(_) => Err(format!("Unknown status for item ID {}.", data.item_id)),
+        Some(1) => Err(CafeHubError::AlreadyClaimed {
+            item_id: data.item_id,
+        }),
+        None => Err(CafeHubError::not_found(Entity::LostItem, data.item_id)),
+        Some(status) => Err(CafeHubError::internal(format!(
+            "Unknown status {} for item ID {}",
+            status, data.item_id
+        ))),
     }
 }
 
 #[tauri::command]
-pub fn admin_send_message(data: SendMessageData, mysql_pool: State<Pool>) -> Result<i32, String> {
+pub fn admin_send_message(
+    token: String,
+    data: SendMessageData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<(), CafeHubError> {
+    let sender_id = sessions
+        .authorize(&token, Permission::SendStaffMessages)?
+        .user_id;
+
     if data.message_content.is_empty() {
-        return Err("Message content cannot be empty".to_string());
+        return Err(CafeHubError::EmptyField {
+            field: "message_content",
+        });
     }
-    if data.sender_id == data.receiver_id {
-        return Err("Sender and receiver cannot be the same user".to_string());
+    if sender_id == data.receiver_id {
+        return Err(CafeHubError::SameSenderAndReceiver);
     }
 
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let sender_exists: Option<i64> = conn
         .exec_first(
             "SELECT id FROM account WHERE id = :id",
-            params! {"id" => data.sender_id},
+            params! {"id" => sender_id},
         )
-        .map_err(|e| format!("Failed to verify sender: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to verify sender", e))?;
     if sender_exists.is_none() {
         println!(
             "Send message failed: Sender with ID {} not found.",
-            data.sender_id
+            sender_id
         );
-        return Ok(1);
+        return Err(CafeHubError::not_found(Entity::User, sender_id));
     }
 
     let receiver_exists: Option<i64> = conn
@@ -986,13 +5436,13 @@ pub fn admin_send_message(data: SendMessageData, mysql_pool: State<Pool>) -> Res
             "SELECT id FROM account WHERE id = :id",
             params! {"id" => data.receiver_id},
         )
-        .map_err(|e| format!("Failed to verify receiver: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to verify receiver", e))?;
     if receiver_exists.is_none() {
         println!(
             "Send message failed: Receiver with ID {} not found.",
             data.receiver_id
         );
-        return Ok(2);
+        return Err(CafeHubError::not_found(Entity::User, data.receiver_id));
     }
 
     let current_date = Local::now().date_naive();
@@ -1001,7 +5451,7 @@ pub fn admin_send_message(data: SendMessageData, mysql_pool: State<Pool>) -> Res
     let result = conn.exec_drop(
         "INSERT INTO message (sender_id, receiver_id, title, message_content, send_date, read_status) VALUES (:sender_id, :receiver_id, :title, :message_content, :send_date, :read_status)",
         params! {
-            "sender_id" => data.sender_id,
+            "sender_id" => sender_id,
             "receiver_id" => data.receiver_id,
             "title" => &data.title,
             "message_content" => &data.message_content,
@@ -1011,26 +5461,37 @@ pub fn admin_send_message(data: SendMessageData, mysql_pool: State<Pool>) -> Res
     );
 
     match result {
-        Ok(_) => Ok(0),
+        Ok(_) => Ok(()),
         Err(e) => {
             eprintln!("Database insert failed for message: {}", e);
-            Err(format!("Database error while sending message: {}", e))
+            Err(CafeHubError::database(
+                "Database error while sending message",
+                e,
+            ))
         }
     }
 }
 
 #[tauri::command]
 pub fn customer_send_message(
+    token: String,
     data: CusSendMessageData,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<i32, String> {
+) -> Result<(), CafeHubError> {
+    let sender_id = sessions
+        .authorize(&token, Permission::ContactStaff)?
+        .user_id;
+
     if data.message_content.is_empty() {
-        return Err("Message content cannot be empty".to_string());
+        return Err(CafeHubError::EmptyField {
+            field: "message_content",
+        });
     }
 
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let admin_id_result: Result<Option<i64>, mysql::Error> =
         conn.exec_first("SELECT id FROM account WHERE user_type = 0 LIMIT 1", ());
@@ -1039,17 +5500,20 @@ pub fn customer_send_message(
         Ok(Some(id)) => id,
         Ok(None) => {
             println!("Send message failed: Administrator account (user_type = 0) not found.");
-            return Ok(2);
+            return Err(CafeHubError::NoStaffAvailable);
         }
         Err(e) => {
             eprintln!("Database error while querying for administrator: {}", e);
-            return Err(format!("Database error finding administrator: {}", e));
+            return Err(CafeHubError::database(
+                "Database error finding administrator",
+                e,
+            ));
         }
     };
 
     let sender_info_result: Result<Option<i8>, mysql::Error> = conn.exec_first(
         "SELECT user_type FROM account WHERE id = :sender_id",
-        params! {"sender_id" => data.sender_id},
+        params! {"sender_id" => sender_id},
     );
 
     match sender_info_result {
@@ -1057,24 +5521,21 @@ pub fn customer_send_message(
             if user_type != 1 {
                 println!(
                     "Send message failed: Sender ID {} is not a customer (user_type: {}).",
-                    data.sender_id, user_type
+                    sender_id, user_type
                 );
-                return Err(format!(
-                    "Sender ID {} is not a customer. Only customers can send messages to the administrator.",
-                    data.sender_id
-                ));
+                return Err(CafeHubError::NotACustomer { user_id: sender_id });
             }
         }
         Ok(None) => {
             println!(
                 "Send message failed: Sender (customer) with ID {} not found.",
-                data.sender_id
+                sender_id
             );
-            return Ok(1);
+            return Err(CafeHubError::not_found(Entity::Customer, sender_id));
         }
         Err(e) => {
             eprintln!("Database error while verifying sender: {}", e);
-            return Err(format!("Database error verifying sender: {}", e));
+            return Err(CafeHubError::database("Database error verifying sender", e));
         }
     }
 
@@ -1084,7 +5545,7 @@ pub fn customer_send_message(
     let result = conn.exec_drop(
         "INSERT INTO message (sender_id, receiver_id, title, message_content, send_date, read_status) VALUES (:sender_id, :receiver_id, :title, :message_content, :send_date, :read_status)",
         params! {
-            "sender_id" => data.sender_id,
+            "sender_id" => sender_id,
             "receiver_id" => admin_id,
             "title" => &data.title,
             "message_content" => &data.message_content,
@@ -1094,22 +5555,28 @@ pub fn customer_send_message(
     );
 
     match result {
-        Ok(_) => Ok(0),
+        Ok(_) => Ok(()),
         Err(e) => {
             eprintln!("Database insert failed for message: {}", e);
-            Err(format!("Database error while sending message: {}", e))
+            Err(CafeHubError::database(
+                "Database error while sending message",
+                e,
+            ))
         }
     }
 }
 
 #[tauri::command]
 pub fn get_sent_messages(
-    user_id: i64,
+    token: String,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<Vec<MessageInfo>, String> {
+) -> Result<Vec<MessageInfo>, CafeHubError> {
+    let user_id = sessions.authorize(&token, Permission::UseMessages)?.user_id;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let query = "
         SELECT
@@ -1150,19 +5617,22 @@ pub fn get_sent_messages(
                 }
             },
         )
-        .map_err(|e| format!("Database query failed for sent messages: {}", e))?;
+        .map_err(|e| CafeHubError::database("Database query failed for sent messages", e))?;
 
     Ok(results)
 }
 
 #[tauri::command]
 pub fn get_recived_messages(
-    user_id: i64,
+    token: String,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<Vec<MessageInfo>, String> {
+) -> Result<Vec<MessageInfo>, CafeHubError> {
+    let user_id = sessions.authorize(&token, Permission::UseMessages)?.user_id;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let query = "
         SELECT
@@ -1203,20 +5673,23 @@ pub fn get_recived_messages(
                 }
             },
         )
-        .map_err(|e| format!("Database query failed for recieved messages: {}", e))?;
+        .map_err(|e| CafeHubError::database("Database query failed for recieved messages", e))?;
 
     Ok(results)
 }
 
 #[tauri::command]
 pub fn mark_message_as_read(
+    token: String,
     data: MarkReadData,
-    current_user_id: i64,
+    sessions: State<SessionStore>,
     mysql_pool: State<Pool>,
-) -> Result<i32, String> {
+) -> Result<(), CafeHubError> {
+    let current_user_id = sessions.authorize(&token, Permission::UseMessages)?.user_id;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let query_result: Result<Option<(i64, i8)>, mysql::Error> = conn.exec_first(
         "SELECT receiver_id, read_status FROM message WHERE id = :message_id",
@@ -1230,7 +5703,9 @@ pub fn mark_message_as_read(
                     "User ID {} attempted to mark message ID {} as read, but is not the receiver (receiver ID {}).",
                     current_user_id, data.message_id, receiver_id_db
                 );
-                return Ok(1);
+                return Err(CafeHubError::NotMessageReceiver {
+                    message_id: data.message_id,
+                });
             }
 
             if read_status_db == 1 {
@@ -1238,7 +5713,7 @@ pub fn mark_message_as_read(
                     "Message ID {} was already read by user ID {}.",
                     data.message_id, current_user_id
                 );
-                return Ok(0);
+                return Ok(());
             }
 
             let update_result = conn.exec_drop(
@@ -1252,16 +5727,16 @@ pub fn mark_message_as_read(
             match update_result {
                 Ok(_) => {
                     if conn.affected_rows() > 0 {
-                        Ok(0)
+                        Ok(())
                     } else {
                         eprintln!(
                             "Failed to mark message ID {} as read for user ID {}: 0 rows affected despite prior checks.",
                             data.message_id, current_user_id
                         );
-                        Err(format!(
-                            "Failed to mark message ID {} as read. The message state might have changed concurrently or an unexpected issue occurred.",
+                        Err(CafeHubError::internal(format!(
+                            "Failed to mark message ID {} as read",
                             data.message_id
-                        ))
+                        )))
                     }
                 }
                 Err(e) => {
@@ -1269,38 +5744,541 @@ pub fn mark_message_as_read(
                         "Database update failed for marking message read (ID {}): {}",
                         data.message_id, e
                     );
-                    Err(format!(
-                        "Database error while marking message as read: {}",
-                        e
+                    Err(CafeHubError::database(
+                        "Database error while marking message as read",
+                        e,
                     ))
                 }
             }
         }
-        Ok(None) => Err(format!("Message with ID {} not found.", data.message_id)),
+        Ok(None) => Err(CafeHubError::not_found(Entity::Message, data.message_id)),
         Err(e) => {
             eprintln!(
                 "Database query failed for message details (ID {}): {}",
                 data.message_id, e
             );
-            Err(format!(
-                "Database query failed to retrieve message details: {}",
-                e
+            Err(CafeHubError::database(
+                "Database query failed to retrieve message details",
+                e,
             ))
         }
     }
 }
 
 #[tauri::command]
-pub fn get_all_users(mysql_pool: State<Pool>) -> Result<Vec<UserBasicInfo>, String> {
+pub fn get_all_users(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<UserBasicInfo>, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewAllUsers)?;
+
     let mut conn = mysql_pool
         .get_conn()
-        .map_err(|e| format!("Failed to get DB connection: {}", e))?;
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
 
     let query = "SELECT id, username FROM account ORDER BY id ASC";
 
     let results: Vec<UserBasicInfo> = conn
         .query_map(query, |(id, username)| UserBasicInfo { id, username })
-        .map_err(|e| format!("Database query failed for fetching all users: {}", e))?;
+        .map_err(|e| CafeHubError::database("Database query failed for fetching all users", e))?;
+
+    Ok(results)
+}
+
+#[tauri::command]
+pub fn setup_status(mysql_pool: State<Pool>) -> Result<SetupStatus, CafeHubError> {
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let schema_version = migrations::current_version(&mut conn)
+        .map_err(|reason| CafeHubError::Migration { reason })?;
+
+    let staff_count: Option<i64> = conn
+        .query_first("SELECT COUNT(*) FROM account WHERE user_type = 0")
+        .map_err(|e| CafeHubError::database("Database query failed for staff accounts", e))?;
+    let has_staff_account = staff_count.unwrap_or(0) > 0;
+
+    Ok(SetupStatus {
+        needs_setup: !has_staff_account,
+        schema_version,
+        has_staff_account,
+    })
+}
+
+#[tauri::command]
+pub fn setup_initialize(
+    data: SetupInitializeData,
+    mysql_pool: State<Pool>,
+) -> Result<Account, CafeHubError> {
+    if data.username.is_empty() {
+        return Err(CafeHubError::EmptyField { field: "username" });
+    }
+    if data.username.chars().count() > 20 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "username",
+            max_length: 20,
+        });
+    }
+    if data.password.len() < 6 {
+        return Err(CafeHubError::PasswordTooShort { min_length: 6 });
+    }
+    if let Some(ref phone_str) = data.phone {
+        if !phone_str.chars().all(|c| c.is_ascii_digit()) || phone_str.len() != 11 {
+            return Err(CafeHubError::InvalidPhone);
+        }
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let hashed_password = hash(&data.password, DEFAULT_COST).map_err(|e| {
+        eprintln!(
+            "Failed to hash password for administrator {}: {}",
+            data.username, e
+        );
+        CafeHubError::internal("Password hashing failed")
+    })?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let staff_count: Option<i64> = tx
+        .query_first("SELECT COUNT(*) FROM account WHERE user_type = 0 FOR UPDATE")
+        .map_err(|e| CafeHubError::database("Database query failed for staff accounts", e))?;
+    if staff_count.unwrap_or(0) > 0 {
+        return Err(CafeHubError::AlreadyInitialized);
+    }
+
+    let current_date = Local::now().date_naive();
+    let user_type: i8 = 0;
+
+    tx.exec_drop(
+        "INSERT INTO account (username, password, phone, join_time, balance, user_type, role_id) VALUES (:username, :password, :phone, :join_time, :balance, :user_type, :role_id)",
+        params! {
+            "username" => &data.username,
+            "password" => &hashed_password,
+            "phone" => &data.phone,
+            "join_time" => current_date,
+            "balance" => Decimal::ZERO,
+            "user_type" => user_type,
+            "role_id" => MANAGER_ROLE_ID,
+        },
+    )
+    .map_err(|e| {
+        eprintln!("Failed to create administrator {}: {}", data.username, e);
+        if let MySQLError::MySqlError(ref mysql_err) = e {
+            if mysql_err.code == 1062 {
+                return CafeHubError::UsernameTaken {
+                    username: data.username.clone(),
+                };
+            }
+        }
+        CafeHubError::database("Database error while creating administrator", e)
+    })?;
+
+    let id = tx
+        .last_insert_id()
+        .ok_or_else(|| CafeHubError::internal("Failed to read new administrator ID"))?
+        as i64;
+
+    let role = permissions::load_account_role(&mut tx, MANAGER_ROLE_ID)
+        .map_err(|e| CafeHubError::database("Database query failed", e))?
+        .ok_or_else(|| CafeHubError::not_found(Entity::Role, MANAGER_ROLE_ID))?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    Ok(Account {
+        id,
+        username: data.username,
+        phone: data.phone,
+        gender: None,
+        join_time: Some(current_date),
+        balance: Some(Decimal::ZERO),
+        points: 0,
+        tier: None,
+        role,
+    })
+}
+
+#[tauri::command]
+pub fn get_schema_version(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<SchemaVersionInfo, CafeHubError> {
+    sessions.authorize(&token, Permission::ViewSystemStatus)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let applied: Vec<AppliedMigration> = migrations::applied_migrations(&mut conn)
+        .map_err(|reason| CafeHubError::Migration { reason })?
+        .into_iter()
+        .map(|(version, name, applied_at)| AppliedMigration {
+            version,
+            name,
+            applied_at,
+        })
+        .collect();
+
+    Ok(SchemaVersionInfo {
+        current_version: applied.last().map(|m| m.version).unwrap_or(0),
+        latest_version: migrations::latest_version(),
+        applied,
+    })
+}
+
+#[tauri::command]
+pub fn get_all_permissions(
+    token: String,
+    sessions: State<SessionStore>,
+) -> Result<Vec<Permission>, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageRoles)?;
+    Ok(permissions::ALL_PERMISSIONS.to_vec())
+}
+
+#[tauri::command]
+pub fn get_roles(
+    token: String,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<Vec<RoleInfo>, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageRoles)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let roles: Vec<(i32, String, i8, i8)> = conn
+        .query("SELECT id, role_name, is_staff, is_builtin FROM role ORDER BY id ASC")
+        .map_err(|e| CafeHubError::database("Database query failed for roles", e))?;
+
+    let mut results: Vec<RoleInfo> = Vec::new();
+    for (id, role_name, is_staff, is_builtin) in roles {
+        let role_permissions = permissions::load_role_permissions(&mut conn, id)
+            .map_err(|e| CafeHubError::database("Database query failed for role permissions", e))?;
+        results.push(RoleInfo {
+            id,
+            role_name,
+            is_staff: is_staff == 1,
+            is_builtin: is_builtin == 1,
+            permissions: role_permissions,
+        });
+    }
 
     Ok(results)
 }
+
+#[tauri::command]
+pub fn create_role(
+    token: String,
+    data: CreateRoleData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<i32, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageRoles)?;
+
+    if data.role_name.is_empty() {
+        return Err(CafeHubError::EmptyField { field: "role_name" });
+    }
+    if data.role_name.chars().count() > 20 {
+        return Err(CafeHubError::FieldTooLong {
+            field: "role_name",
+            max_length: 20,
+        });
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let is_staff: i8 = if data.is_staff { 1 } else { 0 };
+    tx.exec_drop(
+        "INSERT INTO role (role_name, is_staff, is_builtin) VALUES (:role_name, :is_staff, 0)",
+        params! {
+            "role_name" => &data.role_name,
+            "is_staff" => is_staff,
+        },
+    )
+    .map_err(|e| {
+        eprintln!("Database insert failed for role {}: {}", data.role_name, e);
+        if let MySQLError::MySqlError(ref mysql_err) = e {
+            if mysql_err.code == 1062 {
+                return CafeHubError::NameTaken {
+                    entity: Entity::Role,
+                    name: data.role_name.clone(),
+                };
+            }
+        }
+        CafeHubError::database("Database error while creating role", e)
+    })?;
+
+    let role_id =
+        tx.last_insert_id()
+            .ok_or_else(|| CafeHubError::internal("Failed to read new role ID"))? as i32;
+
+    permissions::store_role_permissions(&mut tx, role_id, &data.permissions)
+        .map_err(|e| CafeHubError::database("Failed to store permissions for role", e))?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    Ok(role_id)
+}
+
+#[tauri::command]
+pub fn update_role_permissions(
+    token: String,
+    role_id: i32,
+    data: UpdateRolePermissionsData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<String, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageRoles)?;
+
+    if role_id == MANAGER_ROLE_ID && !data.permissions.contains(&Permission::ManageRoles) {
+        return Err(CafeHubError::ManagerRoleLocked);
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let mut tx = conn
+        .start_transaction(mysql::TxOpts::default())
+        .map_err(|e| CafeHubError::database("Failed to start transaction", e))?;
+
+    let role_exists: Option<i32> = tx
+        .exec_first(
+            "SELECT id FROM role WHERE id = :role_id FOR UPDATE",
+            params! { "role_id" => role_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query role", e))?;
+    if role_exists.is_none() {
+        return Err(CafeHubError::not_found(Entity::Role, role_id));
+    }
+
+    permissions::store_role_permissions(&mut tx, role_id, &data.permissions).map_err(|e| {
+        CafeHubError::database(
+            format!("Failed to store permissions for role ID {}", role_id),
+            e,
+        )
+    })?;
+
+    tx.commit()
+        .map_err(|e| CafeHubError::database("Failed to commit transaction", e))?;
+
+    sessions.set_role_permissions(role_id, &data.permissions);
+
+    Ok(format!(
+        "Permissions for role ID {} updated successfully.",
+        role_id
+    ))
+}
+
+#[tauri::command]
+pub fn delete_role(
+    token: String,
+    role_id: i32,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<String, CafeHubError> {
+    sessions.authorize(&token, Permission::ManageRoles)?;
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let is_builtin: Option<i8> = conn
+        .exec_first(
+            "SELECT is_builtin FROM role WHERE id = :role_id",
+            params! { "role_id" => role_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query role", e))?;
+
+    match is_builtin {
+        Some(0) => {}
+        Some(_) => return Err(CafeHubError::BuiltinRole { role_id }),
+        None => return Err(CafeHubError::not_found(Entity::Role, role_id)),
+    }
+
+    let assigned_accounts: Option<i64> = conn
+        .exec_first(
+            "SELECT COUNT(*) FROM account WHERE role_id = :role_id",
+            params! { "role_id" => role_id },
+        )
+        .map_err(|e| CafeHubError::database("Failed to query accounts for role", e))?;
+    let account_count = assigned_accounts.unwrap_or(0);
+    if account_count > 0 {
+        return Err(CafeHubError::RoleInUse {
+            role_id,
+            account_count,
+        });
+    }
+
+    match conn.exec_drop(
+        "DELETE FROM role WHERE id = :role_id AND is_builtin = 0",
+        params! { "role_id" => role_id },
+    ) {
+        Ok(_) => Ok(format!("Role ID {} deleted successfully.", role_id)),
+        Err(e) => {
+            eprintln!("Database delete failed for role ID {}: {}", role_id, e);
+            Err(CafeHubError::database(
+                "Database error while deleting role",
+                e,
+            ))
+        }
+    }
+}
+
+#[tauri::command]
+pub fn assign_role(
+    token: String,
+    data: AssignRoleData,
+    sessions: State<SessionStore>,
+    mysql_pool: State<Pool>,
+) -> Result<String, CafeHubError> {
+    let session = sessions.authorize(&token, Permission::ManageRoles)?;
+
+    if session.user_id == data.user_id {
+        return Err(CafeHubError::CannotChangeOwnRole);
+    }
+
+    let mut conn = mysql_pool
+        .get_conn()
+        .map_err(|e| CafeHubError::database("Failed to get DB connection", e))?;
+
+    let role = permissions::load_account_role(&mut conn, data.role_id)
+        .map_err(|e| CafeHubError::database("Failed to query role", e))?
+        .ok_or_else(|| CafeHubError::not_found(Entity::Role, data.role_id))?;
+
+    let user_type: i8 = if role.is_staff { 0 } else { 1 };
+
+    conn.exec_drop(
+        "UPDATE account SET role_id = :role_id, user_type = :user_type WHERE id = :user_id",
+        params! {
+            "role_id" => role.id,
+            "user_type" => user_type,
+            "user_id" => data.user_id,
+        },
+    )
+    .map_err(|e| {
+        eprintln!(
+            "Database update failed for role of user ID {}: {}",
+            data.user_id, e
+        );
+        CafeHubError::database("Database error while assigning role", e)
+    })?;
+
+    if conn.affected_rows() == 0 {
+        let user_exists: Option<i64> = conn
+            .exec_first(
+                "SELECT id FROM account WHERE id = :user_id",
+                params! { "user_id" => data.user_id },
+            )
+            .map_err(|e| CafeHubError::database("Failed to query user", e))?;
+        if user_exists.is_none() {
+            return Err(CafeHubError::not_found(Entity::User, data.user_id));
+        }
+    }
+
+    let role_permissions = permissions::load_role_permissions(&mut conn, role.id)
+        .map_err(|e| CafeHubError::database("Database query failed for role permissions", e))?;
+    sessions.set_user_role(data.user_id, role.id, &role_permissions);
+
+    Ok(format!(
+        "User ID {} is now assigned the '{}' role.",
+        data.user_id, role.name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn priced_line(goods_id: i32, line_total: &str) -> PricedLine {
+        PricedLine {
+            goods_id,
+            variant_id: None,
+            variant_stock: false,
+            quantity: 1,
+            unit_price: dec(line_total),
+            discount_amount: Decimal::ZERO,
+            tax_rate: Decimal::ZERO,
+            tax_amount: Decimal::ZERO,
+            line_total: dec(line_total),
+        }
+    }
+
+    #[test]
+    fn allocate_discount_is_proportional() {
+        let mut lines = vec![priced_line(1, "30.00"), priced_line(2, "10.00")];
+        allocate_discount(&mut lines, dec("4.00"));
+        assert_eq!(lines[0].discount_amount, dec("3.00"));
+        assert_eq!(lines[0].line_total, dec("27.00"));
+        assert_eq!(lines[1].discount_amount, dec("1.00"));
+        assert_eq!(lines[1].line_total, dec("9.00"));
+    }
+
+    #[test]
+    fn allocate_discount_places_every_cent() {
+        let mut lines = vec![
+            priced_line(1, "10.00"),
+            priced_line(2, "10.00"),
+            priced_line(3, "10.00"),
+        ];
+        allocate_discount(&mut lines, dec("1.00"));
+        let allocated: Decimal = lines.iter().map(|line| line.discount_amount).sum();
+        assert_eq!(allocated, dec("1.00"));
+        let total: Decimal = lines.iter().map(|line| line.line_total).sum();
+        assert_eq!(total, dec("29.00"));
+    }
+
+    #[test]
+    fn allocate_discount_never_goes_negative() {
+        let mut lines = vec![priced_line(1, "5.00"), priced_line(2, "3.00")];
+        allocate_discount(&mut lines, dec("20.00"));
+        assert!(lines.iter().all(|line| line.line_total == Decimal::ZERO));
+        let allocated: Decimal = lines.iter().map(|line| line.discount_amount).sum();
+        assert_eq!(allocated, dec("8.00"));
+    }
+
+    #[test]
+    fn allocate_discount_ignores_non_positive() {
+        let mut lines = vec![priced_line(1, "5.00")];
+        allocate_discount(&mut lines, Decimal::ZERO);
+        allocate_discount(&mut lines, dec("-1.00"));
+        assert_eq!(lines[0].line_total, dec("5.00"));
+        assert_eq!(lines[0].discount_amount, Decimal::ZERO);
+    }
+
+    #[test]
+    fn like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("latte"), "%latte%");
+        assert_eq!(like_pattern("100%"), "%100\\%%");
+        assert_eq!(like_pattern("a_b"), "%a\\_b%");
+        assert_eq!(like_pattern("c:\\d"), "%c:\\\\d%");
+    }
+
+    #[test]
+    fn page_bounds_defaults_and_clamps() {
+        assert_eq!(page_bounds(None, None), (1, DEFAULT_PAGE_SIZE));
+        assert_eq!(page_bounds(Some(0), Some(0)), (1, 1));
+        assert_eq!(page_bounds(Some(3), Some(50)), (3, 50));
+        assert_eq!(page_bounds(Some(2), Some(1000)), (2, MAX_PAGE_SIZE));
+    }
+}
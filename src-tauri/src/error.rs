@@ -0,0 +1,875 @@
+use crate::permissions::Permission;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entity {
+    User,
+    Customer,
+    Goods,
+    LostItem,
+    Message,
+    Order,
+    OrderItem,
+    Role,
+    RechargeBonusRule,
+    Coupon,
+    MembershipTier,
+    GiftCard,
+    TaxRate,
+    GoodsCategory,
+    GoodsVariant,
+}
+
+impl Entity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Entity::User => "user",
+            Entity::Customer => "customer",
+            Entity::Goods => "goods",
+            Entity::LostItem => "lost_item",
+            Entity::Message => "message",
+            Entity::Order => "order",
+            Entity::OrderItem => "order_item",
+            Entity::Role => "role",
+            Entity::RechargeBonusRule => "recharge_bonus_rule",
+            Entity::Coupon => "coupon",
+            Entity::MembershipTier => "membership_tier",
+            Entity::GiftCard => "gift_card",
+            Entity::TaxRate => "tax_rate",
+            Entity::GoodsCategory => "goods_category",
+            Entity::GoodsVariant => "goods_variant",
+        }
+    }
+
+    fn label_en(self) -> &'static str {
+        match self {
+            Entity::User => "User",
+            Entity::Customer => "Customer",
+            Entity::Goods => "Goods",
+            Entity::LostItem => "Lost item",
+            Entity::Message => "Message",
+            Entity::Order => "Order",
+            Entity::OrderItem => "Order item",
+            Entity::Role => "Role",
+            Entity::RechargeBonusRule => "Recharge bonus rule",
+            Entity::Coupon => "Coupon",
+            Entity::MembershipTier => "Membership tier",
+            Entity::GiftCard => "Gift card",
+            Entity::TaxRate => "Tax rate",
+            Entity::GoodsCategory => "Goods category",
+            Entity::GoodsVariant => "Goods variant",
+        }
+    }
+
+    fn label_zh(self) -> &'static str {
+        match self {
+            Entity::User => "用户",
+            Entity::Customer => "客户",
+            Entity::Goods => "商品",
+            Entity::LostItem => "失物",
+            Entity::Message => "消息",
+            Entity::Order => "订单",
+            Entity::OrderItem => "订单明细",
+            Entity::Role => "角色",
+            Entity::RechargeBonusRule => "充值赠送规则",
+            Entity::Coupon => "优惠券",
+            Entity::MembershipTier => "会员等级",
+            Entity::GiftCard => "礼品卡",
+            Entity::TaxRate => "税率",
+            Entity::GoodsCategory => "商品类别",
+            Entity::GoodsVariant => "商品规格",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    ZhCn,
+}
+
+/// Error returned by every Tauri command.
+///
+/// Serialized as `{ code, message, message_zh, details }`. `code` is stable
+/// and meant for the frontend to branch on; the messages are for display.
+#[derive(Debug, Clone)]
+pub enum CafeHubError {
+    Database {
+        context: String,
+        reason: String,
+    },
+    Internal {
+        reason: String,
+    },
+    Migration {
+        reason: String,
+    },
+    NotAuthenticated,
+    SessionExpired,
+    PermissionDenied {
+        permission: Permission,
+    },
+    InvalidCredentials,
+    IncorrectPassword,
+    EmptyField {
+        field: &'static str,
+    },
+    FieldTooLong {
+        field: &'static str,
+        max_length: usize,
+    },
+    PasswordTooShort {
+        min_length: usize,
+    },
+    InvalidPhone,
+    InvalidGender,
+    NonPositiveAmount {
+        field: &'static str,
+    },
+    NegativeValue {
+        field: &'static str,
+    },
+    InvalidDateRange {
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    },
+    NotFound {
+        entity: Entity,
+        id: i64,
+    },
+    UsernameTaken {
+        username: String,
+    },
+    NameTaken {
+        entity: Entity,
+        name: String,
+    },
+    NoItems,
+    OutOfStock {
+        goods_id: i32,
+        requested: i32,
+        available: i32,
+    },
+    InsufficientBalance {
+        required: Decimal,
+        available: Decimal,
+        shortfall: Decimal,
+    },
+    NotACustomer {
+        user_id: i64,
+    },
+    NothingToRefund {
+        order_id: i64,
+    },
+    RefundQuantityExceeded {
+        order_item_id: i64,
+        requested: i32,
+        refundable: i32,
+    },
+    PercentageOutOfRange {
+        field: &'static str,
+    },
+    InsufficientCash {
+        required: Decimal,
+        tendered: Decimal,
+    },
+    TenderMismatch {
+        total: Decimal,
+        tendered: Decimal,
+    },
+    TenderNotAllowed {
+        tender_type: &'static str,
+    },
+    CouponInvalid {
+        code: String,
+    },
+    CouponExpired {
+        code: String,
+        valid_until: NaiveDate,
+    },
+    CouponMinSpendNotMet {
+        min_spend: Decimal,
+        subtotal: Decimal,
+    },
+    CouponLimitReached {
+        code: String,
+        limit: i32,
+    },
+    CouponNotApplicable {
+        code: String,
+    },
+    CouponInUse {
+        coupon_id: i64,
+        redemption_count: i64,
+    },
+    InsufficientPoints {
+        required: i32,
+        available: i32,
+    },
+    PointsExceedTotal {
+        points: i32,
+        max_points: i32,
+    },
+    PointsNotRedeemable {
+        goods_id: i32,
+    },
+    ZeroAdjustment,
+    GiftCardNotFound {
+        code: String,
+    },
+    GiftCardInsufficient {
+        code: String,
+        required: Decimal,
+        available: Decimal,
+    },
+    GiftCardEmpty {
+        code: String,
+    },
+    GoodsArchived {
+        goods_id: i32,
+    },
+    GoodsInUse {
+        goods_id: i32,
+        order_count: i64,
+        coupon_count: i64,
+    },
+    CategoryInUse {
+        category_id: i32,
+        goods_count: i64,
+    },
+    InvalidPriceRange {
+        min_price: Decimal,
+        max_price: Decimal,
+    },
+    VariantArchived {
+        goods_id: i32,
+        variant_id: i32,
+    },
+    VariantInUse {
+        variant_id: i32,
+        order_count: i64,
+    },
+    VariantPriceNegative {
+        goods_id: i32,
+        variant_id: i32,
+    },
+    AlreadyClaimed {
+        item_id: i64,
+    },
+    NotMessageReceiver {
+        message_id: i64,
+    },
+    SameSenderAndReceiver,
+    NoStaffAvailable,
+    AlreadyInitialized,
+    BuiltinRole {
+        role_id: i32,
+    },
+    RoleInUse {
+        role_id: i32,
+        account_count: i64,
+    },
+    CannotChangeOwnRole,
+    ManagerRoleLocked,
+}
+
+impl CafeHubError {
+    pub fn database(context: impl Into<String>, reason: impl fmt::Display) -> Self {
+        CafeHubError::Database {
+            context: context.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn internal(reason: impl fmt::Display) -> Self {
+        CafeHubError::Internal {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn not_found(entity: Entity, id: impl Into<i64>) -> Self {
+        CafeHubError::NotFound {
+            entity,
+            id: id.into(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            CafeHubError::Database { .. } => "DATABASE_ERROR",
+            CafeHubError::Internal { .. } => "INTERNAL_ERROR",
+            CafeHubError::Migration { .. } => "MIGRATION_FAILED",
+            CafeHubError::NotAuthenticated => "NOT_AUTHENTICATED",
+            CafeHubError::SessionExpired => "SESSION_EXPIRED",
+            CafeHubError::PermissionDenied { .. } => "PERMISSION_DENIED",
+            CafeHubError::InvalidCredentials => "INVALID_CREDENTIALS",
+            CafeHubError::IncorrectPassword => "INCORRECT_PASSWORD",
+            CafeHubError::EmptyField { .. } => "EMPTY_FIELD",
+            CafeHubError::FieldTooLong { .. } => "FIELD_TOO_LONG",
+            CafeHubError::PasswordTooShort { .. } => "PASSWORD_TOO_SHORT",
+            CafeHubError::InvalidPhone => "INVALID_PHONE",
+            CafeHubError::InvalidGender => "INVALID_GENDER",
+            CafeHubError::NonPositiveAmount { .. } => "NON_POSITIVE_AMOUNT",
+            CafeHubError::NegativeValue { .. } => "NEGATIVE_VALUE",
+            CafeHubError::InvalidDateRange { .. } => "INVALID_DATE_RANGE",
+            CafeHubError::NotFound { .. } => "NOT_FOUND",
+            CafeHubError::UsernameTaken { .. } => "USERNAME_TAKEN",
+            CafeHubError::NameTaken { .. } => "NAME_TAKEN",
+            CafeHubError::NoItems => "NO_ITEMS",
+            CafeHubError::OutOfStock { .. } => "OUT_OF_STOCK",
+            CafeHubError::InsufficientBalance { .. } => "INSUFFICIENT_BALANCE",
+            CafeHubError::NotACustomer { .. } => "NOT_A_CUSTOMER",
+            CafeHubError::NothingToRefund { .. } => "NOTHING_TO_REFUND",
+            CafeHubError::RefundQuantityExceeded { .. } => "REFUND_QUANTITY_EXCEEDED",
+            CafeHubError::PercentageOutOfRange { .. } => "PERCENTAGE_OUT_OF_RANGE",
+            CafeHubError::InsufficientCash { .. } => "INSUFFICIENT_CASH",
+            CafeHubError::TenderMismatch { .. } => "TENDER_MISMATCH",
+            CafeHubError::TenderNotAllowed { .. } => "TENDER_NOT_ALLOWED",
+            CafeHubError::CouponInvalid { .. } => "COUPON_INVALID",
+            CafeHubError::CouponExpired { .. } => "COUPON_EXPIRED",
+            CafeHubError::CouponMinSpendNotMet { .. } => "COUPON_MIN_SPEND_NOT_MET",
+            CafeHubError::CouponLimitReached { .. } => "COUPON_LIMIT_REACHED",
+            CafeHubError::CouponNotApplicable { .. } => "COUPON_NOT_APPLICABLE",
+            CafeHubError::CouponInUse { .. } => "COUPON_IN_USE",
+            CafeHubError::InsufficientPoints { .. } => "INSUFFICIENT_POINTS",
+            CafeHubError::PointsExceedTotal { .. } => "POINTS_EXCEED_TOTAL",
+            CafeHubError::PointsNotRedeemable { .. } => "POINTS_NOT_REDEEMABLE",
+            CafeHubError::ZeroAdjustment => "ZERO_ADJUSTMENT",
+            CafeHubError::GiftCardNotFound { .. } => "GIFT_CARD_NOT_FOUND",
+            CafeHubError::GiftCardInsufficient { .. } => "GIFT_CARD_INSUFFICIENT",
+            CafeHubError::GiftCardEmpty { .. } => "GIFT_CARD_EMPTY",
+            CafeHubError::GoodsArchived { .. } => "GOODS_ARCHIVED",
+            CafeHubError::GoodsInUse { .. } => "GOODS_IN_USE",
+            CafeHubError::CategoryInUse { .. } => "CATEGORY_IN_USE",
+            CafeHubError::InvalidPriceRange { .. } => "INVALID_PRICE_RANGE",
+            CafeHubError::VariantArchived { .. } => "VARIANT_ARCHIVED",
+            CafeHubError::VariantInUse { .. } => "VARIANT_IN_USE",
+            CafeHubError::VariantPriceNegative { .. } => "VARIANT_PRICE_NEGATIVE",
+            CafeHubError::AlreadyClaimed { .. } => "ALREADY_CLAIMED",
+            CafeHubError::NotMessageReceiver { .. } => "NOT_MESSAGE_RECEIVER",
+            CafeHubError::SameSenderAndReceiver => "SAME_SENDER_AND_RECEIVER",
+            CafeHubError::NoStaffAvailable => "NO_STAFF_AVAILABLE",
+            CafeHubError::AlreadyInitialized => "ALREADY_INITIALIZED",
+            CafeHubError::BuiltinRole { .. } => "BUILTIN_ROLE",
+            CafeHubError::RoleInUse { .. } => "ROLE_IN_USE",
+            CafeHubError::CannotChangeOwnRole => "CANNOT_CHANGE_OWN_ROLE",
+            CafeHubError::ManagerRoleLocked => "MANAGER_ROLE_LOCKED",
+        }
+    }
+
+    pub fn details(&self) -> Value {
+        match self {
+            CafeHubError::Database { context, reason } => {
+                json!({ "context": context, "reason": reason })
+            }
+            CafeHubError::Internal { reason } | CafeHubError::Migration { reason } => {
+                json!({ "reason": reason })
+            }
+            CafeHubError::PermissionDenied { permission } => json!({ "permission": permission }),
+            CafeHubError::EmptyField { field }
+            | CafeHubError::NonPositiveAmount { field }
+            | CafeHubError::NegativeValue { field } => json!({ "field": field }),
+            CafeHubError::FieldTooLong { field, max_length } => {
+                json!({ "field": field, "max_length": max_length })
+            }
+            CafeHubError::PasswordTooShort { min_length } => json!({ "min_length": min_length }),
+            CafeHubError::InvalidDateRange {
+                start_date,
+                end_date,
+            } => json!({ "start_date": start_date, "end_date": end_date }),
+            CafeHubError::NotFound { entity, id } => {
+                json!({ "entity": entity.as_str(), "id": id })
+            }
+            CafeHubError::UsernameTaken { username } => json!({ "username": username }),
+            CafeHubError::NameTaken { entity, name } => {
+                json!({ "entity": entity.as_str(), "name": name })
+            }
+            CafeHubError::OutOfStock {
+                goods_id,
+                requested,
+                available,
+            } => json!({ "goods_id": goods_id, "requested": requested, "available": available }),
+            CafeHubError::InsufficientBalance {
+                required,
+                available,
+                shortfall,
+            } => json!({ "required": required, "available": available, "shortfall": shortfall }),
+            CafeHubError::NotACustomer { user_id } => json!({ "user_id": user_id }),
+            CafeHubError::NothingToRefund { order_id } => json!({ "order_id": order_id }),
+            CafeHubError::RefundQuantityExceeded {
+                order_item_id,
+                requested,
+                refundable,
+            } => json!({
+                "order_item_id": order_item_id,
+                "requested": requested,
+                "refundable": refundable,
+            }),
+            CafeHubError::PercentageOutOfRange { field } => json!({ "field": field }),
+            CafeHubError::InsufficientCash { required, tendered } => {
+                json!({ "required": required, "tendered": tendered })
+            }
+            CafeHubError::TenderMismatch { total, tendered } => {
+                json!({ "total": total, "tendered": tendered })
+            }
+            CafeHubError::TenderNotAllowed { tender_type } => json!({ "tender_type": tender_type }),
+            CafeHubError::CouponInvalid { code } => json!({ "code": code }),
+            CafeHubError::CouponExpired { code, valid_until } => {
+                json!({ "code": code, "valid_until": valid_until })
+            }
+            CafeHubError::CouponMinSpendNotMet {
+                min_spend,
+                subtotal,
+            } => json!({ "min_spend": min_spend, "subtotal": subtotal }),
+            CafeHubError::CouponLimitReached { code, limit } => {
+                json!({ "code": code, "limit": limit })
+            }
+            CafeHubError::CouponNotApplicable { code } => json!({ "code": code }),
+            CafeHubError::CouponInUse {
+                coupon_id,
+                redemption_count,
+            } => json!({ "coupon_id": coupon_id, "redemption_count": redemption_count }),
+            CafeHubError::InsufficientPoints {
+                required,
+                available,
+            } => json!({ "required": required, "available": available }),
+            CafeHubError::PointsExceedTotal { points, max_points } => {
+                json!({ "points": points, "max_points": max_points })
+            }
+            CafeHubError::PointsNotRedeemable { goods_id } => json!({ "goods_id": goods_id }),
+            CafeHubError::GiftCardNotFound { code } => json!({ "code": code }),
+            CafeHubError::GiftCardInsufficient {
+                code,
+                required,
+                available,
+            } => json!({ "code": code, "required": required, "available": available }),
+            CafeHubError::GiftCardEmpty { code } => json!({ "code": code }),
+            CafeHubError::GoodsArchived { goods_id } => json!({ "goods_id": goods_id }),
+            CafeHubError::GoodsInUse {
+                goods_id,
+                order_count,
+                coupon_count,
+            } => {
+                json!({ "goods_id": goods_id, "order_count": order_count, "coupon_count": coupon_count })
+            }
+            CafeHubError::CategoryInUse {
+                category_id,
+                goods_count,
+            } => json!({ "category_id": category_id, "goods_count": goods_count }),
+            CafeHubError::InvalidPriceRange {
+                min_price,
+                max_price,
+            } => json!({ "min_price": min_price, "max_price": max_price }),
+            CafeHubError::VariantArchived {
+                goods_id,
+                variant_id,
+            } => json!({ "goods_id": goods_id, "variant_id": variant_id }),
+            CafeHubError::VariantInUse {
+                variant_id,
+                order_count,
+            } => json!({ "variant_id": variant_id, "order_count": order_count }),
+            CafeHubError::VariantPriceNegative {
+                goods_id,
+                variant_id,
+            } => json!({ "goods_id": goods_id, "variant_id": variant_id }),
+            CafeHubError::AlreadyClaimed { item_id } => json!({ "item_id": item_id }),
+            CafeHubError::NotMessageReceiver { message_id } => json!({ "message_id": message_id }),
+            CafeHubError::BuiltinRole { role_id } => json!({ "role_id": role_id }),
+            CafeHubError::RoleInUse {
+                role_id,
+                account_count,
+            } => json!({ "role_id": role_id, "account_count": account_count }),
+            CafeHubError::NotAuthenticated
+            | CafeHubError::SessionExpired
+            | CafeHubError::InvalidCredentials
+            | CafeHubError::IncorrectPassword
+            | CafeHubError::InvalidPhone
+            | CafeHubError::InvalidGender
+            | CafeHubError::NoItems
+            | CafeHubError::ZeroAdjustment
+            | CafeHubError::SameSenderAndReceiver
+            | CafeHubError::NoStaffAvailable
+            | CafeHubError::AlreadyInitialized
+            | CafeHubError::CannotChangeOwnRole
+            | CafeHubError::ManagerRoleLocked => Value::Null,
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        match locale {
+            Locale::En => self.message_en(),
+            Locale::ZhCn => self.message_zh(),
+        }
+    }
+
+    fn message_en(&self) -> String {
+        match self {
+            CafeHubError::Database { context, reason } => format!("{}: {}", context, reason),
+            CafeHubError::Internal { reason } => format!("Internal error: {}", reason),
+            CafeHubError::Migration { reason } => format!("Database migration failed: {}", reason),
+            CafeHubError::NotAuthenticated => "Not logged in or session is invalid.".to_string(),
+            CafeHubError::SessionExpired => "Session expired. Please log in again.".to_string(),
+            CafeHubError::PermissionDenied { permission } => format!(
+                "Permission denied: your account is not allowed to perform this action ({}).",
+                permission.as_str()
+            ),
+            CafeHubError::InvalidCredentials => "Invalid username or password".to_string(),
+            CafeHubError::IncorrectPassword => "Incorrect current password".to_string(),
+            CafeHubError::EmptyField { field } => format!("{} cannot be empty", field),
+            CafeHubError::FieldTooLong { field, max_length } => {
+                format!("{} cannot be longer than {} characters", field, max_length)
+            }
+            CafeHubError::PasswordTooShort { min_length } => {
+                format!("Password must be at least {} characters", min_length)
+            }
+            CafeHubError::InvalidPhone => {
+                "Invalid phone number format. Must be 11 digits.".to_string()
+            }
+            CafeHubError::InvalidGender => {
+                "Invalid gender value. Must be 0 (Male) or 1 (Female).".to_string()
+            }
+            CafeHubError::NonPositiveAmount { field } => format!("{} must be positive", field),
+            CafeHubError::NegativeValue { field } => format!("{} cannot be negative", field),
+            CafeHubError::InvalidDateRange {
+                start_date,
+                end_date,
+            } => format!("Start date {} is after end date {}.", start_date, end_date),
+            CafeHubError::NotFound { entity, id } => {
+                format!("{} with ID {} not found.", entity.label_en(), id)
+            }
+            CafeHubError::UsernameTaken { username } => {
+                format!("Username '{}' is already taken.", username)
+            }
+            CafeHubError::NameTaken { entity, name } => {
+                format!("{} with name '{}' already exists.", entity.label_en(), name)
+            }
+            CafeHubError::NoItems => "No items provided for purchase.".to_string(),
+            CafeHubError::OutOfStock {
+                goods_id,
+                requested,
+                available,
+            } => format!(
+                "Insufficient stock for goods ID {}: requested {}, available {}.",
+                goods_id, requested, available
+            ),
+            CafeHubError::InsufficientBalance {
+                required,
+                available,
+                shortfall,
+            } => format!(
+                "Insufficient balance: {} required, {} available ({} short).",
+                required, available, shortfall
+            ),
+            CafeHubError::NotACustomer { user_id } => {
+                format!("User with ID {} is not a customer.", user_id)
+            }
+            CafeHubError::NothingToRefund { order_id } => {
+                format!("Order ID {} has nothing left to refund.", order_id)
+            }
+            CafeHubError::RefundQuantityExceeded {
+                order_item_id,
+                requested,
+                refundable,
+            } => format!(
+                "Cannot refund {} of order item ID {}: only {} left to refund.",
+                requested, order_item_id, refundable
+            ),
+            CafeHubError::PercentageOutOfRange { field } => {
+                format!("{} must be a percentage between 0 and 100.", field)
+            }
+            CafeHubError::InsufficientCash { required, tendered } => format!(
+                "Cash tendered {} is less than the total {}.",
+                tendered, required
+            ),
+            CafeHubError::TenderMismatch { total, tendered } => format!(
+                "Tenders add up to {} but the order total is {}.",
+                tendered, total
+            ),
+            CafeHubError::TenderNotAllowed { tender_type } => {
+                format!(
+                    "Payment by {} is not accepted for this checkout.",
+                    tender_type
+                )
+            }
+            CafeHubError::CouponInvalid { code } => {
+                format!("Coupon '{}' does not exist or is not active.", code)
+            }
+            CafeHubError::CouponExpired { code, valid_until } => {
+                format!("Coupon '{}' expired on {}.", code, valid_until)
+            }
+            CafeHubError::CouponMinSpendNotMet {
+                min_spend,
+                subtotal,
+            } => format!(
+                "This coupon needs a minimum spend of {}, the order is {}.",
+                min_spend, subtotal
+            ),
+            CafeHubError::CouponLimitReached { code, limit } => {
+                format!(
+                    "Coupon '{}' can only be used {} time(s) per customer.",
+                    code, limit
+                )
+            }
+            CafeHubError::CouponNotApplicable { code } => {
+                format!("Coupon '{}' does not apply to this order.", code)
+            }
+            CafeHubError::CouponInUse {
+                coupon_id,
+                redemption_count,
+            } => format!(
+                "Coupon ID {} has been redeemed {} time(s); deactivate it instead.",
+                coupon_id, redemption_count
+            ),
+            CafeHubError::InsufficientPoints {
+                required,
+                available,
+            } => format!(
+                "Not enough points: {} needed, {} available.",
+                required, available
+            ),
+            CafeHubError::PointsExceedTotal { points, max_points } => format!(
+                "{} points is more than this order needs; at most {} can be redeemed.",
+                points, max_points
+            ),
+            CafeHubError::PointsNotRedeemable { goods_id } => format!(
+                "Goods ID {} cannot be redeemed with points in this order.",
+                goods_id
+            ),
+            CafeHubError::ZeroAdjustment => "Adjustment must not be zero.".to_string(),
+            CafeHubError::GiftCardNotFound { code } => {
+                format!("Gift card '{}' not found.", code)
+            }
+            CafeHubError::GiftCardInsufficient {
+                code,
+                required,
+                available,
+            } => format!(
+                "Gift card '{}' has {} left, {} is needed.",
+                code, available, required
+            ),
+            CafeHubError::GiftCardEmpty { code } => {
+                format!("Gift card '{}' has no value left.", code)
+            }
+            CafeHubError::GoodsArchived { goods_id } => {
+                format!("Goods ID {} is no longer on the menu.", goods_id)
+            }
+            CafeHubError::GoodsInUse {
+                goods_id,
+                order_count,
+                coupon_count,
+            } => format!(
+                "Goods ID {} appears in {} order(s) and {} coupon(s); archive it instead.",
+                goods_id, order_count, coupon_count
+            ),
+            CafeHubError::CategoryInUse {
+                category_id,
+                goods_count,
+            } => format!(
+                "Category ID {} still has {} goods; move them or deactivate the category instead.",
+                category_id, goods_count
+            ),
+            CafeHubError::InvalidPriceRange {
+                min_price,
+                max_price,
+            } => format!(
+                "Minimum price {} is above maximum price {}.",
+                min_price, max_price
+            ),
+            CafeHubError::VariantArchived {
+                goods_id,
+                variant_id,
+            } => format!(
+                "Variant ID {} of goods ID {} is no longer on the menu.",
+                variant_id, goods_id
+            ),
+            CafeHubError::VariantInUse {
+                variant_id,
+                order_count,
+            } => format!(
+                "Variant ID {} appears in {} order(s); deactivate it instead.",
+                variant_id, order_count
+            ),
+            CafeHubError::VariantPriceNegative {
+                goods_id,
+                variant_id,
+            } => format!(
+                "Variant ID {} of goods ID {} would cost less than zero.",
+                variant_id, goods_id
+            ),
+            CafeHubError::AlreadyClaimed { item_id } => {
+                format!("Item ID {} has already been claimed.", item_id)
+            }
+            CafeHubError::NotMessageReceiver { message_id } => {
+                format!("You are not the receiver of message ID {}.", message_id)
+            }
+            CafeHubError::SameSenderAndReceiver => {
+                "Sender and receiver cannot be the same user".to_string()
+            }
+            CafeHubError::NoStaffAvailable => {
+                "No staff account is available to receive messages.".to_string()
+            }
+            CafeHubError::AlreadyInitialized => "CafeHub has already been set up.".to_string(),
+            CafeHubError::BuiltinRole { role_id } => {
+                format!("Role ID {} is built in and cannot be deleted.", role_id)
+            }
+            CafeHubError::RoleInUse {
+                role_id,
+                account_count,
+            } => format!(
+                "Role ID {} is still assigned to {} account(s).",
+                role_id, account_count
+            ),
+            CafeHubError::CannotChangeOwnRole => "You cannot change your own role.".to_string(),
+            CafeHubError::ManagerRoleLocked => {
+                "The manager role must keep the manage_roles permission.".to_string()
+            }
+        }
+    }
+
+    fn message_zh(&self) -> String {
+        match self {
+            CafeHubError::Database { .. } => "数据库操作失败，请稍后再试。".to_string(),
+            CafeHubError::Internal { .. } => "系统内部错误，请稍后再试。".to_string(),
+            CafeHubError::Migration { .. } => "数据库升级失败。".to_string(),
+            CafeHubError::NotAuthenticated => "未登录或会话无效，请重新登录。".to_string(),
+            CafeHubError::SessionExpired => "会话已过期，请重新登录。".to_string(),
+            CafeHubError::PermissionDenied { .. } => "您的账户无权执行此操作。".to_string(),
+            CafeHubError::InvalidCredentials => "用户名或密码错误。".to_string(),
+            CafeHubError::IncorrectPassword => "当前密码不正确。".to_string(),
+            CafeHubError::EmptyField { field } => format!("{} 不能为空。", field),
+            CafeHubError::FieldTooLong { field, max_length } => {
+                format!("{} 不能超过 {} 个字符。", field, max_length)
+            }
+            CafeHubError::PasswordTooShort { min_length } => {
+                format!("密码长度不能少于 {} 位。", min_length)
+            }
+            CafeHubError::InvalidPhone => "手机号必须为 11 位数字。".to_string(),
+            CafeHubError::InvalidGender => "性别设置错误。".to_string(),
+            CafeHubError::NonPositiveAmount { field } => format!("{} 必须大于 0。", field),
+            CafeHubError::NegativeValue { field } => format!("{} 不能为负数。", field),
+            CafeHubError::InvalidDateRange { .. } => "开始日期不能晚于结束日期。".to_string(),
+            CafeHubError::NotFound { entity, id } => {
+                format!("未找到 ID 为 {} 的{}。", id, entity.label_zh())
+            }
+            CafeHubError::UsernameTaken { username } => format!("用户名 '{}' 已被占用。", username),
+            CafeHubError::NameTaken { entity, name } => {
+                format!("名为 '{}' 的{}已存在。", name, entity.label_zh())
+            }
+            CafeHubError::NoItems => "购物车为空。".to_string(),
+            CafeHubError::OutOfStock {
+                goods_id,
+                requested,
+                available,
+            } => format!(
+                "商品 {} 库存不足：需要 {}，剩余 {}。",
+                goods_id, requested, available
+            ),
+            CafeHubError::InsufficientBalance { shortfall, .. } => {
+                format!("余额不足，还差 {} 元。", shortfall)
+            }
+            CafeHubError::NotACustomer { user_id } => format!("用户 {} 不是客户账户。", user_id),
+            CafeHubError::NothingToRefund { order_id } => {
+                format!("订单 {} 已无可退款的商品。", order_id)
+            }
+            CafeHubError::RefundQuantityExceeded { refundable, .. } => {
+                format!("退款数量超出可退数量（最多 {}）。", refundable)
+            }
+            CafeHubError::PercentageOutOfRange { field } => {
+                format!("{} 必须是 0 到 100 之间的百分比。", field)
+            }
+            CafeHubError::InsufficientCash { required, tendered } => {
+                format!("实收现金 {} 少于应付金额 {}。", tendered, required)
+            }
+            CafeHubError::TenderMismatch { total, tendered } => {
+                format!("支付金额合计 {} 与订单金额 {} 不符。", tendered, total)
+            }
+            CafeHubError::TenderNotAllowed { tender_type } => {
+                format!("此次结账不接受 {} 支付。", tender_type)
+            }
+            CafeHubError::CouponInvalid { code } => format!("优惠券 '{}' 不存在或未启用。", code),
+            CafeHubError::CouponExpired { code, valid_until } => {
+                format!("优惠券 '{}' 已于 {} 过期。", code, valid_until)
+            }
+            CafeHubError::CouponMinSpendNotMet {
+                min_spend,
+                subtotal,
+            } => format!(
+                "该优惠券需满 {} 元使用，当前订单 {} 元。",
+                min_spend, subtotal
+            ),
+            CafeHubError::CouponLimitReached { code, limit } => {
+                format!("优惠券 '{}' 每位顾客限用 {} 次。", code, limit)
+            }
+            CafeHubError::CouponNotApplicable { code } => {
+                format!("优惠券 '{}' 不适用于该订单。", code)
+            }
+            CafeHubError::CouponInUse { .. } => "该优惠券已被使用，请改为停用。".to_string(),
+            CafeHubError::InsufficientPoints {
+                required,
+                available,
+            } => format!("积分不足：需要 {}，可用 {}。", required, available),
+            CafeHubError::PointsExceedTotal { max_points, .. } => {
+                format!("抵扣积分超过订单金额，最多可使用 {} 积分。", max_points)
+            }
+            CafeHubError::PointsNotRedeemable { goods_id } => {
+                format!("商品 {} 不能在此订单中用积分兑换。", goods_id)
+            }
+            CafeHubError::ZeroAdjustment => "调整数量不能为零。".to_string(),
+            CafeHubError::GiftCardNotFound { code } => format!("未找到礼品卡 '{}'。", code),
+            CafeHubError::GiftCardInsufficient {
+                code, available, ..
+            } => format!("礼品卡 '{}' 余额不足，剩余 {}。", code, available),
+            CafeHubError::GiftCardEmpty { code } => format!("礼品卡 '{}' 已无余额。", code),
+            CafeHubError::GoodsArchived { goods_id } => format!("商品 ID {} 已下架。", goods_id),
+            CafeHubError::GoodsInUse { .. } => "该商品已有历史记录，请改为下架。".to_string(),
+            CafeHubError::CategoryInUse { goods_count, .. } => {
+                format!("该类别下仍有 {} 件商品，请先移走或改为停用。", goods_count)
+            }
+            CafeHubError::InvalidPriceRange {
+                min_price,
+                max_price,
+            } => format!("最低价格 {} 高于最高价格 {}。", min_price, max_price),
+            CafeHubError::VariantArchived { variant_id, .. } => {
+                format!("商品规格 ID {} 已下架。", variant_id)
+            }
+            CafeHubError::VariantInUse { .. } => "该规格已有历史订单，请改为停用。".to_string(),
+            CafeHubError::VariantPriceNegative { variant_id, .. } => {
+                format!("规格 ID {} 的售价会低于零。", variant_id)
+            }
+            CafeHubError::AlreadyClaimed { item_id } => format!("失物 {} 已被认领。", item_id),
+            CafeHubError::NotMessageReceiver { .. } => "您不是该消息的接收者。".to_string(),
+            CafeHubError::SameSenderAndReceiver => "发送者和接收者不能是同一用户。".to_string(),
+            CafeHubError::NoStaffAvailable => "管理员账户未找到，无法发送消息。".to_string(),
+            CafeHubError::AlreadyInitialized => "CafeHub 已完成初始化。".to_string(),
+            CafeHubError::BuiltinRole { .. } => "内置角色不能删除。".to_string(),
+            CafeHubError::RoleInUse { account_count, .. } => {
+                format!("该角色仍被 {} 个账户使用。", account_count)
+            }
+            CafeHubError::CannotChangeOwnRole => "不能修改自己的角色。".to_string(),
+            CafeHubError::ManagerRoleLocked => "店长角色必须保留角色管理权限。".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CafeHubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message_en())
+    }
+}
+
+impl std::error::Error for CafeHubError {}
+
+impl Serialize for CafeHubError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CafeHubError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.message(Locale::En))?;
+        state.serialize_field("message_zh", &self.message(Locale::ZhCn))?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
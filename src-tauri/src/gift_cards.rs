@@ -0,0 +1,88 @@
+use chrono::Local;
+use mysql::{params, prelude::Queryable};
+use rand::{rngs::OsRng, Rng};
+use rust_decimal::Decimal;
+
+/// Letters and digits that cannot be misread on a printed card.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GiftCardTxnType {
+    Issue,
+    Load,
+    Redeem,
+    Refund,
+    Transfer,
+}
+
+impl GiftCardTxnType {
+    /// Name stored in `gift_card_transactions.txn_type`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GiftCardTxnType::Issue => "issue",
+            GiftCardTxnType::Load => "load",
+            GiftCardTxnType::Redeem => "redeem",
+            GiftCardTxnType::Refund => "refund",
+            GiftCardTxnType::Transfer => "transfer",
+        }
+    }
+}
+
+/// One change to a gift card's value. `amount` is signed and
+/// `balance_after` is what the card was left with.
+pub struct GiftCardEntry<'a> {
+    pub gift_card_id: i64,
+    pub txn_type: GiftCardTxnType,
+    pub amount: Decimal,
+    pub balance_after: Decimal,
+    pub payment_method: Option<&'a str>,
+    pub order_id: Option<i64>,
+    pub refund_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub operator_id: Option<i64>,
+}
+
+/// Codes are matched case-insensitively and stored upper-case.
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+/// A random code in the form `XXXX-XXXX-XXXX-XXXX`.
+pub fn generate_code() -> String {
+    (0..4)
+        .map(|_| {
+            (0..4)
+                .map(|_| CODE_ALPHABET[OsRng.gen_range(0..CODE_ALPHABET.len())] as char)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Sets the card's balance and appends `entry` to its history. Call it on
+/// the transaction that holds the card's row lock.
+pub fn record<Q: Queryable>(conn: &mut Q, entry: &GiftCardEntry) -> Result<(), mysql::Error> {
+    conn.exec_drop(
+        "UPDATE gift_cards SET balance = :balance WHERE id = :gift_card_id",
+        params! {
+            "balance" => entry.balance_after,
+            "gift_card_id" => entry.gift_card_id,
+        },
+    )?;
+    conn.exec_drop(
+        "INSERT INTO gift_card_transactions (gift_card_id, txn_type, amount, balance_after, payment_method, order_id, refund_id, user_id, operator_id, created_at)
+         VALUES (:gift_card_id, :txn_type, :amount, :balance_after, :payment_method, :order_id, :refund_id, :user_id, :operator_id, :created_at)",
+        params! {
+            "gift_card_id" => entry.gift_card_id,
+            "txn_type" => entry.txn_type.as_str(),
+            "amount" => entry.amount,
+            "balance_after" => entry.balance_after,
+            "payment_method" => entry.payment_method,
+            "order_id" => entry.order_id,
+            "refund_id" => entry.refund_id,
+            "user_id" => entry.user_id,
+            "operator_id" => entry.operator_id,
+            "created_at" => Local::now().naive_local(),
+        },
+    )
+}
@@ -0,0 +1,63 @@
+use chrono::Local;
+use mysql::{params, prelude::Queryable};
+use rust_decimal::Decimal;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionType {
+    Recharge,
+    RechargeBonus,
+    Purchase,
+    Refund,
+    GiftCardTransfer,
+}
+
+impl TransactionType {
+    /// Name stored in `balance_transactions.txn_type`.
+    /// Migration 0006 also wrote `opening` rows for pre-ledger balances.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TransactionType::Recharge => "recharge",
+            TransactionType::RechargeBonus => "recharge_bonus",
+            TransactionType::Purchase => "purchase",
+            TransactionType::Refund => "refund",
+            TransactionType::GiftCardTransfer => "gift_card_transfer",
+        }
+    }
+}
+
+/// One balance change. `amount` is signed and `balance_after` is the
+/// balance the account was left with.
+pub struct LedgerEntry<'a> {
+    pub user_id: i64,
+    pub txn_type: TransactionType,
+    pub amount: Decimal,
+    pub balance_after: Decimal,
+    pub operator_id: Option<i64>,
+    pub order_id: Option<i64>,
+    pub refund_id: Option<i64>,
+    pub recharge_id: Option<i64>,
+    pub gift_card_id: Option<i64>,
+    pub note: Option<&'a str>,
+}
+
+/// Appends `entry` to the ledger. Call it on the same transaction that
+/// changes `account.balance` so the two can never disagree.
+pub fn record<Q: Queryable>(conn: &mut Q, entry: &LedgerEntry) -> Result<(), mysql::Error> {
+    conn.exec_drop(
+        "INSERT INTO balance_transactions (user_id, txn_type, amount, balance_after, operator_id, order_id, refund_id, recharge_id, gift_card_id, note, created_at)
+         VALUES (:user_id, :txn_type, :amount, :balance_after, :operator_id, :order_id, :refund_id, :recharge_id, :gift_card_id, :note, :created_at)",
+        params! {
+            "user_id" => entry.user_id,
+            "txn_type" => entry.txn_type.as_str(),
+            "amount" => entry.amount,
+            "balance_after" => entry.balance_after,
+            "operator_id" => entry.operator_id,
+            "order_id" => entry.order_id,
+            "refund_id" => entry.refund_id,
+            "recharge_id" => entry.recharge_id,
+            "gift_card_id" => entry.gift_card_id,
+            "note" => entry.note,
+            "created_at" => Local::now().naive_local(),
+        },
+    )
+}
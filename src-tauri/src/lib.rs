@@ -1,29 +1,44 @@
 mod commands;
 mod db_config;
+mod error;
+mod gift_cards;
+mod ledger;
+mod loyalty;
+mod migrations;
 mod models;
+mod permissions;
+mod session;
+mod tax;
+mod tiers;
 
 use db_config::MySQLConfig;
-use mysql::{Opts, OptsBuilder, Pool};
+use mysql::Pool;
+use session::SessionStore;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let mysql_config: MySQLConfig = MySQLConfig::new(
-        "root".to_string(),
-        "123456".to_string(),
-        "localhost".to_string(),
-        "cafehub".to_string(),
-    );
-
-    let mysql_url = mysql_config.format_url();
-    let pool_options =
-        OptsBuilder::from_opts(Opts::from_url(&mysql_url).expect("Invalid database URL"));
-    let pool = Pool::new(pool_options).expect("Failed to create DB pool.");
-
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(pool)
+        .manage(SessionStore::default())
+        .setup(|app| {
+            let config_dir = app.path().app_config_dir()?;
+            let mysql_config = MySQLConfig::load(&config_dir)?;
+            let pool = Pool::new(mysql_config.to_opts()?)
+                .map_err(|e| format!("Failed to connect to database {}: {}", mysql_config, e))?;
+            let mut conn = pool
+                .get_conn()
+                .map_err(|e| format!("Failed to connect to database {}: {}", mysql_config, e))?;
+            let schema_version = migrations::run_pending(&mut conn)
+                .map_err(|e| format!("Failed to migrate database {}: {}", mysql_config, e))?;
+            println!("Database schema is at version {}", schema_version);
+            drop(conn);
+            app.manage(pool);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::login,
+            commands::logout,
             commands::register_user,
             commands::get_total_users,
             commands::get_new_users_this_month,
@@ -34,9 +49,42 @@ pub fn run() {
             commands::update_user_details,
             commands::update_user_password,
             commands::get_all_goods,
+            commands::search_goods,
             commands::add_goods,
             commands::update_goods_info,
+            commands::archive_goods,
+            commands::restore_goods,
+            commands::delete_goods,
+            commands::get_goods_variants,
+            commands::create_goods_variant,
+            commands::update_goods_variant,
+            commands::delete_goods_variant,
+            commands::get_goods_categories,
+            commands::create_goods_category,
+            commands::update_goods_category,
+            commands::delete_goods_category,
+            commands::get_tax_settings,
+            commands::update_tax_settings,
+            commands::create_tax_rate,
+            commands::update_tax_rate,
+            commands::delete_tax_rate,
+            commands::get_tax_report,
             commands::purchase_goods,
+            commands::walk_in_checkout,
+            commands::get_user_orders,
+            commands::get_order_detail,
+            commands::refund_order,
+            commands::get_balance_history,
+            commands::check_balance_consistency,
+            commands::adjust_points,
+            commands::get_points_history,
+            commands::get_membership_tiers,
+            commands::update_membership_tier,
+            commands::recompute_membership_tiers,
+            commands::issue_gift_card,
+            commands::load_gift_card,
+            commands::get_gift_card,
+            commands::transfer_gift_card_to_balance,
             commands::get_all_lost_items,
             commands::report_lost_item,
             commands::claim_lost_item,
@@ -46,7 +94,24 @@ pub fn run() {
             commands::get_recived_messages,
             commands::mark_message_as_read,
             commands::get_all_users,
-            commands::recharge_balance
+            commands::recharge_balance,
+            commands::get_recharge_bonus_rules,
+            commands::create_recharge_bonus_rule,
+            commands::update_recharge_bonus_rule,
+            commands::delete_recharge_bonus_rule,
+            commands::get_coupons,
+            commands::create_coupon,
+            commands::update_coupon,
+            commands::delete_coupon,
+            commands::setup_status,
+            commands::setup_initialize,
+            commands::get_schema_version,
+            commands::get_all_permissions,
+            commands::get_roles,
+            commands::create_role,
+            commands::update_role_permissions,
+            commands::delete_role,
+            commands::assign_role
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
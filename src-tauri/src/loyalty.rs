@@ -0,0 +1,215 @@
+use chrono::{Duration, Local, NaiveDate};
+use mysql::{params, prelude::Queryable};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// Points earned per whole unit of currency paid for an order.
+pub const POINTS_PER_UNIT: i64 = 1;
+/// Discount given for each point redeemed (100 points = 1.00).
+pub const POINT_VALUE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+/// Points expire this many days after they were earned.
+pub const POINTS_VALID_DAYS: i64 = 365;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointsTxnType {
+    Earn,
+    Redeem,
+    Expire,
+    Adjust,
+    Refund,
+}
+
+impl PointsTxnType {
+    /// Name stored in `points_transactions.txn_type`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PointsTxnType::Earn => "earn",
+            PointsTxnType::Redeem => "redeem",
+            PointsTxnType::Expire => "expire",
+            PointsTxnType::Adjust => "adjust",
+            PointsTxnType::Refund => "refund",
+        }
+    }
+}
+
+/// One change to an account's points. Positive entries open a lot that
+/// expires after `POINTS_VALID_DAYS`; negative ones draw down the oldest lots.
+pub struct PointsEntry<'a> {
+    pub user_id: i64,
+    pub txn_type: PointsTxnType,
+    pub points: i32,
+    pub points_after: i32,
+    pub operator_id: Option<i64>,
+    pub order_id: Option<i64>,
+    pub note: Option<&'a str>,
+}
+
+/// Points earned for paying `amount`.
+pub fn points_for(amount: Decimal) -> i32 {
+    (amount.floor() * Decimal::from(POINTS_PER_UNIT))
+        .to_i32()
+        .unwrap_or(0)
+        .max(0)
+}
+
+/// The part of an order's `points` that goes with the first `refunded` of its
+/// `total`. Cumulative, so a full refund always comes to exactly `points`.
+pub fn refunded_share(points: i32, refunded: Decimal, total: Decimal) -> i32 {
+    if points <= 0 || total <= Decimal::ZERO || refunded <= Decimal::ZERO {
+        return 0;
+    }
+    if refunded >= total {
+        return points;
+    }
+    (Decimal::from(points) * refunded / total)
+        .round()
+        .to_i32()
+        .unwrap_or(0)
+        .clamp(0, points)
+}
+
+/// Appends `entry` to the points ledger and, for redemptions and negative
+/// adjustments, consumes lots oldest first. Run it on the transaction that holds the account row lock
+/// and updates `account.points`.
+pub fn record<Q: Queryable>(conn: &mut Q, entry: &PointsEntry) -> Result<(), mysql::Error> {
+    let today = Local::now().date_naive();
+    let (remaining, expires_at) = if entry.points > 0 {
+        (
+            Some(entry.points),
+            Some(today + Duration::days(POINTS_VALID_DAYS)),
+        )
+    } else {
+        (None, None)
+    };
+
+    if entry.points < 0 && entry.txn_type != PointsTxnType::Expire {
+        consume_lots(conn, entry.user_id, -entry.points, today)?;
+    }
+
+    conn.exec_drop(
+        "INSERT INTO points_transactions (user_id, txn_type, points, points_after, remaining, expires_at, operator_id, order_id, note, created_at)
+         VALUES (:user_id, :txn_type, :points, :points_after, :remaining, :expires_at, :operator_id, :order_id, :note, :created_at)",
+        params! {
+            "user_id" => entry.user_id,
+            "txn_type" => entry.txn_type.as_str(),
+            "points" => entry.points,
+            "points_after" => entry.points_after,
+            "remaining" => remaining,
+            "expires_at" => expires_at,
+            "operator_id" => entry.operator_id,
+            "order_id" => entry.order_id,
+            "note" => entry.note,
+            "created_at" => Local::now().naive_local(),
+        },
+    )
+}
+
+fn consume_lots<Q: Queryable>(
+    conn: &mut Q,
+    user_id: i64,
+    mut points: i32,
+    today: NaiveDate,
+) -> Result<(), mysql::Error> {
+    let lots: Vec<(i64, i32)> = conn.exec(
+        "SELECT id, remaining FROM points_transactions
+         WHERE user_id = :user_id AND remaining > 0 AND expires_at >= :today
+         ORDER BY expires_at ASC, id ASC FOR UPDATE",
+        params! { "user_id" => user_id, "today" => today },
+    )?;
+    for (lot_id, remaining) in lots {
+        if points == 0 {
+            break;
+        }
+        let used = remaining.min(points);
+        conn.exec_drop(
+            "UPDATE points_transactions SET remaining = remaining - :used WHERE id = :lot_id",
+            params! { "used" => used, "lot_id" => lot_id },
+        )?;
+        points -= used;
+    }
+    Ok(())
+}
+
+/// Writes off lots that expired before today and returns the account's
+/// points afterwards. The caller must hold the account row lock.
+pub fn expire_points<Q: Queryable>(conn: &mut Q, user_id: i64) -> Result<i32, mysql::Error> {
+    let today = Local::now().date_naive();
+    let points: i32 = conn
+        .exec_first(
+            "SELECT points FROM account WHERE id = :user_id",
+            params! { "user_id" => user_id },
+        )?
+        .unwrap_or(0);
+    let expired: Option<i64> = conn.exec_first(
+        "SELECT CAST(COALESCE(SUM(remaining), 0) AS SIGNED) FROM points_transactions
+         WHERE user_id = :user_id AND remaining > 0 AND expires_at < :today",
+        params! { "user_id" => user_id, "today" => today },
+    )?;
+    let expired = expired.unwrap_or(0) as i32;
+    if expired == 0 {
+        return Ok(points);
+    }
+
+    conn.exec_drop(
+        "UPDATE points_transactions SET remaining = 0
+         WHERE user_id = :user_id AND remaining > 0 AND expires_at < :today",
+        params! { "user_id" => user_id, "today" => today },
+    )?;
+    let points_after = (points - expired).max(0);
+    conn.exec_drop(
+        "UPDATE account SET points = :points WHERE id = :user_id",
+        params! { "points" => points_after, "user_id" => user_id },
+    )?;
+    record(
+        conn,
+        &PointsEntry {
+            user_id,
+            txn_type: PointsTxnType::Expire,
+            points: points_after - points,
+            points_after,
+            operator_id: None,
+            order_id: None,
+            note: None,
+        },
+    )?;
+    Ok(points_after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn points_for_whole_units_only() {
+        assert_eq!(points_for(dec("25.99")), 25);
+        assert_eq!(points_for(dec("0.99")), 0);
+        assert_eq!(points_for(Decimal::ZERO), 0);
+        assert_eq!(points_for(dec("-5.00")), 0);
+    }
+
+    #[test]
+    fn refunded_share_is_proportional() {
+        assert_eq!(refunded_share(100, dec("25.00"), dec("100.00")), 25);
+        assert_eq!(refunded_share(7, dec("1.00"), dec("3.00")), 2);
+        assert_eq!(refunded_share(100, Decimal::ZERO, dec("100.00")), 0);
+        assert_eq!(refunded_share(0, dec("50.00"), dec("100.00")), 0);
+        assert_eq!(refunded_share(10, dec("1.00"), Decimal::ZERO), 0);
+    }
+
+    #[test]
+    fn refunded_share_adds_up_over_partial_refunds() {
+        let total = dec("3.00");
+        let mut reversed = 0;
+        let mut refunded = Decimal::ZERO;
+        for _ in 0..3 {
+            let before = refunded_share(10, refunded, total);
+            refunded += dec("1.00");
+            reversed += refunded_share(10, refunded, total) - before;
+        }
+        assert_eq!(reversed, 10);
+    }
+}
@@ -0,0 +1,365 @@
+//! Versioned schema migrations, embedded from `migrations/NNNN_name.sql`.
+//!
+//! MySQL cannot roll back DDL, so a migration that fails halfway leaves its
+//! earlier statements applied. Progress is therefore recorded per statement
+//! in `schema_migration_progress`, and a retry resumes at the statement that
+//! failed instead of re-running the ones that already succeeded. Each
+//! statement on its own must still be safe to retry once fixed; keep them to
+//! a single DDL change or an autocommitted DML statement.
+
+use chrono::NaiveDateTime;
+use mysql::{params, prelude::Queryable};
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// All schema migrations, in the order they are applied. Never edit a
+/// migration that has shipped; add a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        sql: include_str!("../migrations/0001_initial_schema.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "roles",
+        sql: include_str!("../migrations/0002_roles.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "orders",
+        sql: include_str!("../migrations/0003_orders.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "order_receipts",
+        sql: include_str!("../migrations/0004_order_receipts.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "refunds",
+        sql: include_str!("../migrations/0005_refunds.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "balance_ledger",
+        sql: include_str!("../migrations/0006_balance_ledger.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "recharges",
+        sql: include_str!("../migrations/0007_recharges.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "recharge_bonus_rules",
+        sql: include_str!("../migrations/0008_recharge_bonus_rules.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "walk_in_sales",
+        sql: include_str!("../migrations/0009_walk_in_sales.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "order_tenders",
+        sql: include_str!("../migrations/0010_order_tenders.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "coupons",
+        sql: include_str!("../migrations/0011_coupons.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "loyalty_points",
+        sql: include_str!("../migrations/0012_loyalty_points.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "membership_tiers",
+        sql: include_str!("../migrations/0013_membership_tiers.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "gift_cards",
+        sql: include_str!("../migrations/0014_gift_cards.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "tax_rates",
+        sql: include_str!("../migrations/0015_tax_rates.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "archive_goods",
+        sql: include_str!("../migrations/0016_archive_goods.sql"),
+    },
+    Migration {
+        version: 17,
+        name: "goods_categories",
+        sql: include_str!("../migrations/0017_goods_categories.sql"),
+    },
+    Migration {
+        version: 18,
+        name: "goods_variants",
+        sql: include_str!("../migrations/0018_goods_variants.sql"),
+    },
+];
+
+const MIGRATION_LOCK: &str = "cafehub_schema_migrations";
+const MIGRATION_LOCK_TIMEOUT_SECS: u32 = 30;
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Splits a SQL script into individual statements, dropping `--` comments.
+/// Quoted strings and identifiers are copied as they are, so `;` and `--`
+/// inside them are kept.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            current.push(c);
+            if c == '\\' && q != '`' {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            } else if c == q {
+                // A doubled quote simply opens a new quoted run.
+                quote = None;
+            }
+            continue;
+        }
+
+        match c {
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                current.push(c);
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                for skipped in chars.by_ref() {
+                    if skipped == '\n' {
+                        current.push('\n');
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                push_statement(&mut statements, &current);
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    push_statement(&mut statements, &current);
+
+    statements
+}
+
+fn push_statement(statements: &mut Vec<String>, stmt: &str) {
+    let stmt = stmt.trim();
+    if !stmt.is_empty() {
+        statements.push(stmt.to_string());
+    }
+}
+
+fn ensure_version_table<Q: Queryable>(conn: &mut Q) -> Result<(), mysql::Error> {
+    conn.query_drop(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INT PRIMARY KEY,
+            name VARCHAR(100) NOT NULL,
+            applied_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )?;
+    conn.query_drop(
+        "CREATE TABLE IF NOT EXISTS schema_migration_progress (
+            version INT PRIMARY KEY,
+            statements_applied INT NOT NULL
+        )",
+    )
+}
+
+/// Reads the recorded schema version without touching the schema; startup
+/// has already created the bookkeeping tables by the time this is called.
+pub fn current_version<Q: Queryable>(conn: &mut Q) -> Result<u32, String> {
+    let version: Option<Option<u32>> = conn
+        .query_first("SELECT MAX(version) FROM schema_version")
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+    Ok(version.flatten().unwrap_or(0))
+}
+
+pub fn applied_migrations<Q: Queryable>(
+    conn: &mut Q,
+) -> Result<Vec<(u32, String, NaiveDateTime)>, String> {
+    conn.query("SELECT version, name, applied_at FROM schema_version ORDER BY version ASC")
+        .map_err(|e| format!("Failed to read applied migrations: {}", e))
+}
+
+/// Brings the database up to `latest_version()`, returning the resulting
+/// version. A named lock keeps two app instances from migrating at once.
+pub fn run_pending<Q: Queryable>(conn: &mut Q) -> Result<u32, String> {
+    let locked: Option<Option<i32>> = conn
+        .exec_first(
+            "SELECT GET_LOCK(:name, :timeout)",
+            params! { "name" => MIGRATION_LOCK, "timeout" => MIGRATION_LOCK_TIMEOUT_SECS },
+        )
+        .map_err(|e| format!("Failed to acquire migration lock: {}", e))?;
+    if locked.flatten() != Some(1) {
+        return Err(
+            "Timed out waiting for another CafeHub instance to finish migrating the database"
+                .to_string(),
+        );
+    }
+
+    let result = apply_migrations(conn);
+
+    if let Err(e) = conn.exec_drop(
+        "SELECT RELEASE_LOCK(:name)",
+        params! { "name" => MIGRATION_LOCK },
+    ) {
+        eprintln!("Failed to release migration lock: {}", e);
+    }
+
+    result
+}
+
+fn apply_migrations<Q: Queryable>(conn: &mut Q) -> Result<u32, String> {
+    ensure_version_table(conn)
+        .map_err(|e| format!("Failed to create schema_version table: {}", e))?;
+    let start_version = current_version(conn)?;
+    let latest = latest_version();
+
+    if start_version > latest {
+        return Err(format!(
+            "Database schema version {} is newer than this build of CafeHub supports ({}). Please upgrade CafeHub.",
+            start_version, latest
+        ));
+    }
+
+    let mut version = start_version;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > start_version) {
+        println!(
+            "Applying migration {:04}_{}",
+            migration.version, migration.name
+        );
+        // Statements that went through before an earlier failed attempt.
+        let already_applied: Option<usize> = conn
+            .exec_first(
+                "SELECT statements_applied FROM schema_migration_progress WHERE version = :version",
+                params! { "version" => migration.version },
+            )
+            .map_err(|e| format!("Failed to read migration progress: {}", e))?;
+        let already_applied = already_applied.unwrap_or(0);
+        if already_applied > 0 {
+            println!(
+                "Resuming migration {:04}_{} after statement {}",
+                migration.version, migration.name, already_applied
+            );
+        }
+
+        let statements = split_statements(migration.sql);
+        for (index, stmt) in statements.iter().enumerate().skip(already_applied) {
+            conn.query_drop(stmt).map_err(|e| {
+                format!(
+                    "Migration {:04}_{} failed at statement {}: {}",
+                    migration.version,
+                    migration.name,
+                    index + 1,
+                    e
+                )
+            })?;
+            conn.exec_drop(
+                "INSERT INTO schema_migration_progress (version, statements_applied)
+                 VALUES (:version, :applied)
+                 ON DUPLICATE KEY UPDATE statements_applied = :applied",
+                params! { "version" => migration.version, "applied" => index + 1 },
+            )
+            .map_err(|e| format!("Failed to record migration progress: {}", e))?;
+        }
+
+        conn.exec_drop(
+            "INSERT INTO schema_version (version, name) VALUES (:version, :name)",
+            params! { "version" => migration.version, "name" => migration.name },
+        )
+        .map_err(|e| format!("Failed to record migration {}: {}", migration.version, e))?;
+        conn.exec_drop(
+            "DELETE FROM schema_migration_progress WHERE version = :version",
+            params! { "version" => migration.version },
+        )
+        .map_err(|e| format!("Failed to clear migration progress: {}", e))?;
+        version = migration.version;
+    }
+
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_semicolons_and_drops_comments() {
+        let sql = "-- header\nCREATE TABLE a (id INT); -- trailing\n\nINSERT INTO a VALUES (1);\n-- footer";
+        assert_eq!(
+            split_statements(sql),
+            vec!["CREATE TABLE a (id INT)", "INSERT INTO a VALUES (1)"]
+        );
+    }
+
+    #[test]
+    fn keeps_separators_inside_quotes() {
+        let sql = "INSERT INTO t VALUES ('a;b', \"--not a comment\");\nSELECT `odd;name` FROM t";
+        assert_eq!(
+            split_statements(sql),
+            vec![
+                "INSERT INTO t VALUES ('a;b', \"--not a comment\")",
+                "SELECT `odd;name` FROM t"
+            ]
+        );
+    }
+
+    #[test]
+    fn handles_escaped_and_doubled_quotes() {
+        let sql = "INSERT INTO t VALUES ('it''s; fine'); INSERT INTO t VALUES ('a\\'; b')";
+        assert_eq!(
+            split_statements(sql),
+            vec![
+                "INSERT INTO t VALUES ('it''s; fine')",
+                "INSERT INTO t VALUES ('a\\'; b')"
+            ]
+        );
+    }
+
+    #[test]
+    fn last_statement_needs_no_semicolon() {
+        assert_eq!(
+            split_statements("SELECT 1;\n  SELECT 2  "),
+            vec!["SELECT 1", "SELECT 2"]
+        );
+        assert!(split_statements("  -- only a comment\n;").is_empty());
+    }
+
+    #[test]
+    fn migrations_are_ordered_and_split_cleanly() {
+        for pair in MIGRATIONS.windows(2) {
+            assert_eq!(pair[1].version, pair[0].version + 1);
+        }
+        for migration in MIGRATIONS {
+            let statements = split_statements(migration.sql);
+            assert!(!statements.is_empty(), "{} is empty", migration.name);
+            for stmt in statements {
+                assert!(!stmt.contains("--"), "{}: {}", migration.name, stmt);
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
-use chrono::NaiveDate;
+use crate::permissions::Permission;
+use chrono::{NaiveDate, NaiveDateTime};
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Account {
@@ -10,7 +11,30 @@ pub struct Account {
     pub gender: Option<i8>,
     pub join_time: Option<NaiveDate>,
     pub balance: Option<Decimal>,
-    pub user_type: i8,
+    pub points: i32,
+    pub tier: Option<AccountTier>, // Customers only
+    pub role: AccountRole,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountTier {
+    pub id: i32,
+    pub name: String,
+    pub discount_percent: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountRole {
+    pub id: i32,
+    pub name: String,
+    pub is_staff: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LoginResponse {
+    pub token: String,
+    pub expires_in_secs: u64,
+    pub account: Account,
 }
 
 #[derive(Deserialize)]
@@ -25,11 +49,13 @@ pub struct RegistrationData {
 pub struct MonthlyConsumptionSummary {
     pub month: String, // "YYYY-MM"
     pub total_amount: Decimal,
+    pub tax_amount: Decimal, // Part of total_amount
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct GoodsConsumptionShare {
     pub goods_name: String,
+    pub variant_name: Option<String>, // Only set when split by variant
     pub amount: Decimal,
 }
 
@@ -50,41 +76,694 @@ pub struct UpdatePasswordData {
 pub struct Goods {
     pub id: i32,
     pub goods_name: String,
-    pub goods_type: Option<String>,
+    pub goods_type: Option<String>, // Name of the category
+    pub category_id: Option<i32>,
     pub price: Decimal,
     pub stock: Option<i32>,
+    pub points_price: Option<i32>, // Points to redeem one unit, NULL if not redeemable
+    pub is_active: bool,           // False once archived
 }
 
 #[derive(Deserialize)]
 pub struct AddGoodsData {
     pub goods_name: String,
-    pub goods_type: Option<String>,
+    pub category_id: Option<i32>,
     pub price: Decimal,
     pub stock: Option<i32>,
+    pub points_price: Option<i32>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct GoodsCategory {
+    pub id: i32,
+    pub name: String,
+    pub display_order: i32,
+    pub icon: Option<String>,
+    pub is_active: bool,
+}
+
+#[derive(Deserialize)]
+pub struct GoodsCategoryData {
+    pub name: String,
+    pub display_order: i32,
+    pub icon: Option<String>,
+    pub is_active: bool,
+}
+
+#[derive(Serialize)]
+pub struct GoodsCategoryGroup {
+    pub category: Option<GoodsCategory>, // None for goods without a category
+    pub goods: Vec<Goods>,
+}
+
+/// `get_all_goods` returns a flat list, or groups when asked for them.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum GoodsListing {
+    Flat(Vec<Goods>),
+    Grouped(Vec<GoodsCategoryGroup>),
+}
+
+#[derive(Serialize, Clone)]
+pub struct GoodsVariant {
+    pub id: i32,
+    pub goods_id: i32,
+    pub variant_name: String,
+    pub price_delta: Decimal, // Added to the parent's price; may be negative
+    pub sku: Option<String>,
+    pub stock: Option<i32>, // None draws on the parent's stock
+    pub display_order: i32,
+    pub is_active: bool,
+}
+
+#[derive(Deserialize)]
+pub struct GoodsVariantData {
+    pub variant_name: String,
+    pub price_delta: Decimal,
+    pub sku: Option<String>,
+    pub stock: Option<i32>,
+    pub display_order: i32,
+    pub is_active: bool,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum GoodsSortKey {
+    Id,
+    Name,
+    Price,
+    Stock,
+    Category, // By the category's display order
+}
+
+#[derive(Deserialize)]
+pub struct GoodsQuery {
+    pub search: Option<String>, // Matched anywhere in the name
+    pub category_id: Option<i32>,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>, // Inclusive
+    pub in_stock_only: Option<bool>,
+    pub include_archived: Option<bool>, // Needs manage_goods
+    pub sort_by: Option<GoodsSortKey>,  // Defaults to id
+    pub descending: Option<bool>,
+    pub page: Option<u32>, // 1-based
+    pub page_size: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct GoodsPage {
+    pub goods: Vec<Goods>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+/// Tells an explicit `null` (`Some(None)`) apart from a missing field
+/// (`None`) for optional columns that can be cleared.
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Deserialize)]
 pub struct UpdateGoodsData {
+    pub goods_name: Option<String>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub category_id: Option<Option<i32>>, // null clears; otherwise an existing category
     pub stock: Option<i32>,
     pub price: Option<Decimal>,
+    pub points_price: Option<i32>, // 0 makes the goods no longer redeemable
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMethod {
+    Cash,
+    Card,
+    Transfer,
+}
+
+impl PaymentMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PaymentMethod::Cash => "cash",
+            PaymentMethod::Card => "card",
+            PaymentMethod::Transfer => "transfer",
+        }
+    }
 }
 
 #[derive(Deserialize)]
 pub struct RechargeBalanceData {
     pub user_id: i64,
     pub amount: Decimal,
+    pub payment_method: PaymentMethod,
+    pub note: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RechargeResult {
+    pub recharge_id: i64,
+    pub user_id: i64,
+    pub amount: Decimal,
+    pub payment_method: PaymentMethod,
+    pub bonus_amount: Decimal,
+    pub bonus_rule_id: Option<i64>,
+    pub balance_before: Decimal,
+    pub balance_after: Decimal,
+    pub operator_id: i64,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BonusType {
+    Fixed,
+    Percent,
+}
+
+impl BonusType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BonusType::Fixed => "fixed",
+            BonusType::Percent => "percent",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BonusType> {
+        match name {
+            "fixed" => Some(BonusType::Fixed),
+            "percent" => Some(BonusType::Percent),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct RechargeBonusRule {
+    pub id: i64,
+    pub name: String,
+    pub min_amount: Decimal,
+    pub bonus_type: BonusType,
+    pub bonus_value: Decimal,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_until: Option<NaiveDate>,
+    pub is_active: bool,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct RechargeBonusRuleData {
+    pub name: String,
+    pub min_amount: Decimal,
+    pub bonus_type: BonusType,
+    pub bonus_value: Decimal,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_until: Option<NaiveDate>,
+    pub is_active: bool,
 }
 
 #[derive(Deserialize, Clone)]
 pub struct PurchaseItem {
     pub goods_id: i32,
+    pub variant_id: Option<i32>, // Must belong to goods_id
     pub quantity: i32,
 }
 
 #[derive(Deserialize)]
 pub struct PurchaseGoodsData {
+    pub items: Vec<PurchaseItem>,
+    pub tenders: Option<Vec<Tender>>, // Balance and gift card only; None pays all from balance
+    pub coupon_code: Option<String>,
+    pub redeem_points: Option<PointsRedemption>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PointsRedemption {
+    Discount { points: i32 },   // Worth loyalty::POINT_VALUE each
+    FreeItem { goods_id: i32 }, // One unit in the order, for the goods' points_price
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TenderType {
+    Balance,
+    Cash,
+    Card,
+    Transfer,
+    GiftCard,
+}
+
+impl TenderType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TenderType::Balance => "balance",
+            TenderType::Cash => "cash",
+            TenderType::Card => "card",
+            TenderType::Transfer => "transfer",
+            TenderType::GiftCard => "gift_card",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Tender {
+    pub tender_type: TenderType,
+    pub amount: Decimal,
+    pub gift_card_code: Option<String>, // Required for gift_card tenders
+}
+
+#[derive(Serialize)]
+pub struct PurchaseResult {
+    pub order_id: i64,
+    pub item_count: i32, // total quantity across all lines
+    pub subtotal: Decimal,
+    pub discount_amount: Decimal,
+    pub tier_discount: Decimal, // Part of discount_amount
+    pub tax_amount: Decimal,
+    pub prices_include_tax: bool, // If false, tax_amount was added on top
+    pub total_amount: Decimal,
+    pub coupon_code: Option<String>,
+    pub tenders: Vec<Tender>,
+    pub balance_after: Decimal,
+    pub points_redeemed: i32,
+    pub points_earned: i32,
+    pub points_after: i32,
+}
+
+#[derive(Serialize)]
+pub struct MembershipTier {
+    pub id: i32,
+    pub name: String,
+    pub min_spend: Decimal, // Net spend over tiers::TIER_WINDOW_DAYS
+    pub discount_percent: Decimal,
+    pub member_count: i64,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMembershipTierData {
+    pub min_spend: Decimal,
+    pub discount_percent: Decimal,
+}
+
+#[derive(Serialize)]
+pub struct TierRecomputeResult {
+    pub customers: i64,
+    pub changed: u64,
+}
+
+#[derive(Serialize)]
+pub struct TaxSettings {
+    pub prices_include_tax: bool, // If false, tax is added on top of goods prices
+    pub default_rate_percent: Decimal,
+    pub rates: Vec<TaxRate>,
+}
+
+#[derive(Serialize)]
+pub struct TaxRate {
+    pub id: i32,
+    pub category_id: i32,
+    pub category_name: String,
+    pub rate_percent: Decimal,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTaxSettingsData {
+    pub prices_include_tax: bool,
+    pub default_rate_percent: Decimal,
+}
+
+#[derive(Deserialize)]
+pub struct TaxRateData {
+    pub category_id: i32,
+    pub rate_percent: Decimal,
+}
+
+#[derive(Deserialize)]
+pub struct TaxReportQuery {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate, // Inclusive
+}
+
+#[derive(Serialize)]
+pub struct TaxReportLine {
+    pub tax_rate: Decimal,
+    pub sales_amount: Decimal, // What customers paid, including tax
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
+}
+
+#[derive(Serialize)]
+pub struct TaxReport {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub lines: Vec<TaxReportLine>,
+    pub sales_amount: Decimal,
+    pub net_amount: Decimal,
+    pub tax_amount: Decimal,
+}
+
+#[derive(Deserialize)]
+pub struct IssueGiftCardData {
+    pub code: Option<String>, // Generated when omitted
+    pub initial_value: Decimal,
+    pub payment_method: PaymentMethod,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoadGiftCardData {
+    pub code: String,
+    pub amount: Decimal,
+    pub payment_method: PaymentMethod,
+}
+
+#[derive(Serialize)]
+pub struct GiftCard {
+    pub id: i64,
+    pub code: String,
+    pub initial_value: Decimal,
+    pub balance: Decimal,
+    pub note: Option<String>,
+    pub issued_by: i64,
+    pub issued_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct GiftCardTransaction {
+    pub id: i64,
+    pub txn_type: String, // issue, load, redeem, refund, transfer
+    pub amount: Decimal,  // Signed: credits are positive, debits negative
+    pub balance_after: Decimal,
+    pub payment_method: Option<String>,
+    pub order_id: Option<i64>,
+    pub refund_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub operator_id: Option<i64>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct GiftCardDetail {
+    pub card: GiftCard,
+    pub transactions: Vec<GiftCardTransaction>,
+}
+
+#[derive(Deserialize)]
+pub struct GiftCardTransferData {
+    pub code: String,
     pub user_id: i64,
+}
+
+#[derive(Serialize)]
+pub struct GiftCardTransferResult {
+    pub gift_card_id: i64,
+    pub user_id: i64,
+    pub amount: Decimal,
+    pub balance_after: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscountType {
+    Percent,
+    Fixed,
+    FreeItem,
+}
+
+impl DiscountType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiscountType::Percent => "percent",
+            DiscountType::Fixed => "fixed",
+            DiscountType::FreeItem => "free_item",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DiscountType> {
+        match name {
+            "percent" => Some(DiscountType::Percent),
+            "fixed" => Some(DiscountType::Fixed),
+            "free_item" => Some(DiscountType::FreeItem),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Coupon {
+    pub id: i64,
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: DiscountType,
+    pub discount_value: Option<Decimal>, // NULL for free_item
+    pub free_goods_id: Option<i32>,
+    pub min_spend: Decimal,
+    pub per_customer_limit: Option<i32>,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_until: Option<NaiveDate>,
+    pub is_active: bool,
+    pub redemption_count: i64,
+}
+
+#[derive(Deserialize)]
+pub struct CouponData {
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: DiscountType,
+    pub discount_value: Option<Decimal>, // Percentage for percent, amount for fixed
+    pub free_goods_id: Option<i32>,      // Required for free_item
+    pub min_spend: Option<Decimal>,
+    pub per_customer_limit: Option<i32>,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_until: Option<NaiveDate>,
+    pub is_active: bool,
+}
+
+#[derive(Deserialize)]
+pub struct WalkInCheckoutData {
     pub items: Vec<PurchaseItem>,
+    pub customer_id: Option<i64>, // Member at the counter; required for balance tenders
+    pub tenders: Vec<Tender>,
+    pub cash_tendered: Option<Decimal>, // Handed over for the cash tenders; omitted means exact change
+}
+
+#[derive(Serialize)]
+pub struct WalkInCheckoutResult {
+    pub order_id: i64,
+    pub customer_id: Option<i64>,
+    pub item_count: i32,
+    pub subtotal: Decimal,
+    pub tax_amount: Decimal, // Part of total_amount
+    pub total_amount: Decimal,
+    pub payment_method: String, // balance, cash, card, transfer, or split
+    pub tenders: Vec<Tender>,
+    pub cash_tendered: Option<Decimal>,
+    pub change_due: Option<Decimal>,
+    pub balance_after: Option<Decimal>, // NULL without a customer
+}
+
+#[derive(Deserialize)]
+pub struct OrderQuery {
+    pub user_id: Option<i64>, // Defaults to the caller; other users need view_all_orders
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>, // Inclusive
+    pub page: Option<u32>,           // 1-based
+    pub page_size: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct OrderSummary {
+    pub id: i64,
+    pub user_id: i64,
+    pub item_count: i64,
+    pub subtotal: Decimal,
+    pub discount_amount: Decimal,
+    pub total_amount: Decimal,
+    pub refunded_amount: Decimal,
+    pub status: i8, // 0: Completed, 1: Partially refunded, 2: Refunded
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct OrderPage {
+    pub orders: Vec<OrderSummary>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+#[derive(Serialize)]
+pub struct OrderItemDetail {
+    pub id: i64,
+    pub goods_id: i32,
+    pub goods_name: String,
+    pub variant_name: Option<String>,
+    pub quantity: i32,
+    pub refunded_quantity: i32,
+    pub unit_price: Decimal,
+    pub discount_amount: Decimal,
+    pub tax_rate: Decimal,
+    pub tax_amount: Decimal, // Part of line_total
+    pub line_total: Decimal,
+    pub refunded_amount: Decimal,
+}
+
+#[derive(Serialize)]
+pub struct OrderDetail {
+    pub id: i64,
+    pub user_id: Option<i64>, // NULL for walk-in sales
+    pub username: Option<String>,
+    pub items: Vec<OrderItemDetail>,
+    pub subtotal: Decimal,
+    pub discount_amount: Decimal,
+    pub coupon_code: Option<String>,
+    pub tax_amount: Decimal, // Part of total_amount
+    pub prices_include_tax: bool,
+    pub total_amount: Decimal,
+    pub refunded_amount: Decimal,
+    pub status: i8, // 0: Completed, 1: Partially refunded, 2: Refunded
+    pub payment: OrderPayment,
+    pub balance_before: Option<Decimal>, // NULL for orders migrated from monthly totals and walk-in sales
+    pub balance_after: Option<Decimal>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Default)]
+pub struct OrderPayment {
+    pub method: String, // balance, cash, card, transfer, or split
+    pub tenders: Vec<OrderTender>,
+    pub cash_tendered: Option<Decimal>,
+    pub change_due: Option<Decimal>,
+    pub cashier_id: Option<i64>,
+    pub cashier_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct OrderTender {
+    pub tender_type: String,
+    pub amount: Decimal,
+}
+
+#[derive(Deserialize)]
+pub struct RefundItem {
+    pub order_item_id: i64,
+    pub quantity: i32,
+}
+
+#[derive(Deserialize)]
+pub struct RefundOrderData {
+    pub order_id: i64,
+    pub reason: String,
+    pub items: Option<Vec<RefundItem>>, // None refunds everything not yet refunded
+}
+
+#[derive(Serialize)]
+pub struct RefundResult {
+    pub refund_id: i64,
+    pub order_id: i64,
+    pub refund_amount: Decimal,
+    pub balance_refund: Decimal,   // Credited to the member balance
+    pub gift_card_refund: Decimal, // Credited back to gift cards; the rest is paid out at the till
+    pub order_status: i8,
+    pub balance_after: Option<Decimal>, // NULL when nothing went back to a balance
+    pub points_returned: i32,           // Redeemed points given back
+    pub points_reversed: i32,           // Earned points taken back
+    pub points_after: Option<i32>,      // NULL when no points changed
+}
+
+#[derive(Deserialize)]
+pub struct PointsAdjustmentData {
+    pub user_id: i64,
+    pub points: i32, // Signed
+    pub note: String,
+}
+
+#[derive(Serialize)]
+pub struct PointsAdjustmentResult {
+    pub user_id: i64,
+    pub points: i32,
+    pub points_after: i32,
+}
+
+#[derive(Deserialize)]
+pub struct PointsHistoryQuery {
+    pub user_id: Option<i64>, // Defaults to the caller; other users need view_all_users
+    pub page: Option<u32>,    // 1-based
+    pub page_size: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct PointsTransaction {
+    pub id: i64,
+    pub txn_type: String, // earn, redeem, expire, adjust
+    pub points: i32,
+    pub points_after: i32,
+    pub expires_at: Option<NaiveDate>,
+    pub order_id: Option<i64>,
+    pub operator_id: Option<i64>,
+    pub operator_name: Option<String>,
+    pub note: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct PointsHistoryPage {
+    pub user_id: i64,
+    pub points: i32,
+    pub transactions: Vec<PointsTransaction>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+#[derive(Deserialize)]
+pub struct BalanceHistoryQuery {
+    pub user_id: Option<i64>, // Defaults to the caller; other users need recharge_balance
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>, // Inclusive
+    pub page: Option<u32>,           // 1-based
+    pub page_size: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct BalanceTransaction {
+    pub id: i64,
+    pub txn_type: String, // opening, recharge, recharge_bonus, purchase, refund, gift_card_transfer
+    pub amount: Decimal,  // Signed: credits are positive, debits negative
+    pub balance_after: Decimal,
+    pub operator_id: Option<i64>,
+    pub operator_name: Option<String>,
+    pub order_id: Option<i64>,
+    pub refund_id: Option<i64>,
+    pub recharge_id: Option<i64>,
+    pub gift_card_id: Option<i64>,
+    pub note: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub struct BalanceHistoryPage {
+    pub user_id: i64,
+    pub current_balance: Decimal,
+    pub ledger_balance: Decimal, // Sum of every ledger entry; equals current_balance when consistent
+    pub transactions: Vec<BalanceTransaction>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+#[derive(Serialize)]
+pub struct BalanceDiscrepancy {
+    pub user_id: i64,
+    pub username: String,
+    pub stored_balance: Decimal,
+    pub ledger_balance: Decimal,
+    pub difference: Decimal, // stored_balance - ledger_balance
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -105,18 +784,15 @@ pub struct LostItem {
 pub struct ReportLostItemData {
     pub item_name: String,
     pub pick_place: Option<String>,
-    pub pick_user_id: Option<i64>,
 }
 
 #[derive(Deserialize)]
 pub struct ClaimLostItemData {
     pub item_id: i64,
-    pub claim_user_id: i64,
 }
 
 #[derive(Deserialize)]
 pub struct SendMessageData {
-    pub sender_id: i64,
     pub receiver_id: i64,
     pub title: Option<String>,
     pub message_content: String,
@@ -124,7 +800,6 @@ pub struct SendMessageData {
 
 #[derive(Deserialize)]
 pub struct CusSendMessageData {
-    pub sender_id: i64,
     pub title: Option<String>,
     pub message_content: String,
 }
@@ -152,3 +827,58 @@ pub struct UserBasicInfo {
     pub id: i64,
     pub username: String,
 }
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SetupStatus {
+    pub needs_setup: bool,
+    pub schema_version: u32,
+    pub has_staff_account: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SetupInitializeData {
+    pub username: String,
+    pub password: String,
+    pub phone: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub applied_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SchemaVersionInfo {
+    pub current_version: u32,
+    pub latest_version: u32,
+    pub applied: Vec<AppliedMigration>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RoleInfo {
+    pub id: i32,
+    pub role_name: String,
+    pub is_staff: bool,
+    pub is_builtin: bool,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateRoleData {
+    pub role_name: String,
+    pub is_staff: bool,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateRolePermissionsData {
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Deserialize)]
+pub struct AssignRoleData {
+    pub user_id: i64,
+    pub role_id: i32,
+}
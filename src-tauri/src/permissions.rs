@@ -0,0 +1,152 @@
+use crate::models::AccountRole;
+use mysql::{params, prelude::Queryable};
+use serde::{Deserialize, Serialize};
+
+/// Built-in roles seeded by migration 0002. Their names and flags cannot be
+/// changed, but their permission sets can.
+pub const MANAGER_ROLE_ID: i32 = 1;
+pub const CUSTOMER_ROLE_ID: i32 = 4;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ViewAnalytics,
+    ViewAllUsers,
+    ViewAllOrders,
+    RefundOrders,
+    WalkInSales,
+    ManageGoods,
+    ManageTaxes,
+    RechargeBalance,
+    ManageGiftCards,
+    AdjustPoints,
+    ManagePromotions,
+    SendStaffMessages,
+    ViewSystemStatus,
+    ManageRoles,
+    ManageOwnAccount,
+    Purchase,
+    ClaimLostItem,
+    ContactStaff,
+    ViewGoods,
+    ViewLostItems,
+    ReportLostItem,
+    UseMessages,
+}
+
+pub const ALL_PERMISSIONS: &[Permission] = &[
+    Permission::ViewAnalytics,
+    Permission::ViewAllUsers,
+    Permission::ViewAllOrders,
+    Permission::RefundOrders,
+    Permission::WalkInSales,
+    Permission::ManageGoods,
+    Permission::ManageTaxes,
+    Permission::RechargeBalance,
+    Permission::ManageGiftCards,
+    Permission::AdjustPoints,
+    Permission::ManagePromotions,
+    Permission::SendStaffMessages,
+    Permission::ViewSystemStatus,
+    Permission::ManageRoles,
+    Permission::ManageOwnAccount,
+    Permission::Purchase,
+    Permission::ClaimLostItem,
+    Permission::ContactStaff,
+    Permission::ViewGoods,
+    Permission::ViewLostItems,
+    Permission::ReportLostItem,
+    Permission::UseMessages,
+];
+
+impl Permission {
+    /// Name stored in `role_permission.permission`; matches the serde name.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::ViewAnalytics => "view_analytics",
+            Permission::ViewAllUsers => "view_all_users",
+            Permission::ViewAllOrders => "view_all_orders",
+            Permission::RefundOrders => "refund_orders",
+            Permission::WalkInSales => "walk_in_sales",
+            Permission::ManageGoods => "manage_goods",
+            Permission::ManageTaxes => "manage_taxes",
+            Permission::RechargeBalance => "recharge_balance",
+            Permission::ManageGiftCards => "manage_gift_cards",
+            Permission::AdjustPoints => "adjust_points",
+            Permission::ManagePromotions => "manage_promotions",
+            Permission::SendStaffMessages => "send_staff_messages",
+            Permission::ViewSystemStatus => "view_system_status",
+            Permission::ManageRoles => "manage_roles",
+            Permission::ManageOwnAccount => "manage_own_account",
+            Permission::Purchase => "purchase",
+            Permission::ClaimLostItem => "claim_lost_item",
+            Permission::ContactStaff => "contact_staff",
+            Permission::ViewGoods => "view_goods",
+            Permission::ViewLostItems => "view_lost_items",
+            Permission::ReportLostItem => "report_lost_item",
+            Permission::UseMessages => "use_messages",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Permission> {
+        ALL_PERMISSIONS.iter().copied().find(|p| p.as_str() == name)
+    }
+}
+
+pub fn load_account_role<Q: Queryable>(
+    conn: &mut Q,
+    role_id: i32,
+) -> Result<Option<AccountRole>, mysql::Error> {
+    let role: Option<(i32, String, i8)> = conn.exec_first(
+        "SELECT id, role_name, is_staff FROM role WHERE id = :role_id",
+        params! { "role_id" => role_id },
+    )?;
+    Ok(role.map(|(id, name, is_staff)| AccountRole {
+        id,
+        name,
+        is_staff: is_staff == 1,
+    }))
+}
+
+/// Loads the permission set of `role_id`, skipping names this build does
+/// not know about.
+pub fn load_role_permissions<Q: Queryable>(
+    conn: &mut Q,
+    role_id: i32,
+) -> Result<Vec<Permission>, mysql::Error> {
+    let names: Vec<String> = conn.exec(
+        "SELECT permission FROM role_permission WHERE role_id = :role_id",
+        params! { "role_id" => role_id },
+    )?;
+
+    Ok(names
+        .iter()
+        .filter_map(|name| {
+            let permission = Permission::from_name(name);
+            if permission.is_none() {
+                eprintln!("Ignoring unknown permission '{}' on role {}", name, role_id);
+            }
+            permission
+        })
+        .collect())
+}
+
+pub fn store_role_permissions<Q: Queryable>(
+    conn: &mut Q,
+    role_id: i32,
+    permissions: &[Permission],
+) -> Result<(), mysql::Error> {
+    conn.exec_drop(
+        "DELETE FROM role_permission WHERE role_id = :role_id",
+        params! { "role_id" => role_id },
+    )?;
+    conn.exec_batch(
+        "INSERT IGNORE INTO role_permission (role_id, permission) VALUES (:role_id, :permission)",
+        permissions.iter().map(|p| {
+            params! {
+                "role_id" => role_id,
+                "permission" => p.as_str(),
+            }
+        }),
+    )
+}
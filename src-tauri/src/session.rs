@@ -0,0 +1,106 @@
+use crate::error::CafeHubError;
+use crate::permissions::Permission;
+use rand::{rngs::OsRng, RngCore};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Sessions expire after this long without being used.
+pub const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(8 * 60 * 60);
+
+#[derive(Clone)]
+pub struct Session {
+    pub user_id: i64,
+    pub role_id: i32,
+    pub permissions: Vec<Permission>,
+    expires_at: Instant,
+}
+
+/// In-memory session tokens issued by `login`, managed as Tauri state.
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionStore {
+    pub fn create(&self, user_id: i64, role_id: i32, permissions: Vec<Permission>) -> String {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(
+            token.clone(),
+            Session {
+                user_id,
+                role_id,
+                permissions,
+                expires_at: now + SESSION_IDLE_TIMEOUT,
+            },
+        );
+        token
+    }
+
+    /// Resolves the session behind `token` and extends its expiry.
+    pub fn authenticate(&self, token: &str) -> Result<Session, CafeHubError> {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        match sessions.get_mut(token) {
+            Some(session) if session.expires_at > now => {
+                session.expires_at = now + SESSION_IDLE_TIMEOUT;
+                Ok(session.clone())
+            }
+            Some(_) => {
+                sessions.remove(token);
+                Err(CafeHubError::SessionExpired)
+            }
+            None => Err(CafeHubError::NotAuthenticated),
+        }
+    }
+
+    /// Like `authenticate`, but also requires the session's role to grant
+    /// `permission`. This is the guard every command goes through.
+    pub fn authorize(&self, token: &str, permission: Permission) -> Result<Session, CafeHubError> {
+        let session = self.authenticate(token)?;
+        if !session.permissions.contains(&permission) {
+            println!(
+                "Permission denied: user ID {} (role {}) lacks {}",
+                session.user_id,
+                session.role_id,
+                permission.as_str()
+            );
+            return Err(CafeHubError::PermissionDenied { permission });
+        }
+        Ok(session)
+    }
+
+    /// Applies a role assignment to the user's live sessions.
+    pub fn set_user_role(&self, user_id: i64, role_id: i32, permissions: &[Permission]) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        for session in sessions.values_mut().filter(|s| s.user_id == user_id) {
+            session.role_id = role_id;
+            session.permissions = permissions.to_vec();
+        }
+    }
+
+    /// Applies a changed permission set to every live session of `role_id`.
+    pub fn set_role_permissions(&self, role_id: i32, permissions: &[Permission]) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        for session in sessions.values_mut().filter(|s| s.role_id == role_id) {
+            session.permissions = permissions.to_vec();
+        }
+    }
+
+    pub fn revoke(&self, token: &str) -> bool {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(token).is_some()
+    }
+
+    /// Ends every session of `user_id` except `keep_token`.
+    pub fn revoke_other_sessions(&self, user_id: i64, keep_token: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|token, session| session.user_id != user_id || token == keep_token);
+    }
+}
@@ -0,0 +1,63 @@
+use mysql::{params, prelude::Queryable};
+use rust_decimal::Decimal;
+
+/// Whether goods prices already include tax, from the `tax_settings` row.
+pub fn prices_include_tax<Q: Queryable>(conn: &mut Q) -> Result<bool, mysql::Error> {
+    let include: Option<bool> =
+        conn.query_first("SELECT prices_include_tax FROM tax_settings WHERE id = 1")?;
+    Ok(include.unwrap_or(true))
+}
+
+/// Rate charged on `goods_id`: its category's rate, else the default.
+pub fn rate_for_goods<Q: Queryable>(conn: &mut Q, goods_id: i32) -> Result<Decimal, mysql::Error> {
+    let rate: Option<Option<Decimal>> = conn.exec_first(
+        "SELECT COALESCE(
+                (SELECT r.rate_percent FROM tax_rates r WHERE r.category_id = g.category_id),
+                (SELECT s.default_rate_percent FROM tax_settings s WHERE s.id = 1))
+         FROM goods g WHERE g.id = :goods_id",
+        params! { "goods_id" => goods_id },
+    )?;
+    Ok(rate.flatten().unwrap_or(Decimal::ZERO))
+}
+
+/// Tax on `amount` at `rate_percent`. Inclusive amounts already contain it;
+/// exclusive ones have it added on top.
+pub fn line_tax(amount: Decimal, rate_percent: Decimal, prices_include_tax: bool) -> Decimal {
+    if rate_percent <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    let tax = if prices_include_tax {
+        amount * rate_percent / (Decimal::ONE_HUNDRED + rate_percent)
+    } else {
+        amount * rate_percent / Decimal::ONE_HUNDRED
+    };
+    tax.round_dp(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn inclusive_tax_is_the_share_of_the_price() {
+        assert_eq!(line_tax(dec("113.00"), dec("13"), true), dec("13.00"));
+        assert_eq!(line_tax(dec("10.00"), dec("6"), true), dec("0.57"));
+    }
+
+    #[test]
+    fn exclusive_tax_is_added_on_top() {
+        assert_eq!(line_tax(dec("100.00"), dec("13"), false), dec("13.00"));
+        assert_eq!(line_tax(dec("9.99"), dec("6"), false), dec("0.60"));
+    }
+
+    #[test]
+    fn no_tax_without_a_positive_rate() {
+        assert_eq!(line_tax(dec("50.00"), Decimal::ZERO, true), Decimal::ZERO);
+        assert_eq!(line_tax(dec("50.00"), dec("-5"), false), Decimal::ZERO);
+        assert_eq!(line_tax(Decimal::ZERO, dec("13"), false), Decimal::ZERO);
+    }
+}
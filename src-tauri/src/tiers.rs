@@ -0,0 +1,57 @@
+use crate::models::AccountTier;
+use chrono::{Duration, Local, NaiveDateTime};
+use mysql::{params, prelude::Queryable};
+use rust_decimal::Decimal;
+
+/// Tiers are based on net spend over this many days.
+pub const TIER_WINDOW_DAYS: i64 = 365;
+
+/// Picks each customer's tier from their net spend in the window. Shared by
+/// the per-customer and the bulk recompute so both agree.
+const TIER_FOR_ACCOUNT: &str = "(SELECT t.id FROM membership_tiers t
+     WHERE t.min_spend <= (
+         SELECT COALESCE(SUM(o.total_amount - o.refunded_amount), 0) FROM orders o
+         WHERE o.user_id = account.id AND o.created_at >= :since
+     )
+     ORDER BY t.min_spend DESC, t.id DESC LIMIT 1)";
+
+fn window_start() -> NaiveDateTime {
+    Local::now().naive_local() - Duration::days(TIER_WINDOW_DAYS)
+}
+
+pub fn load_account_tier<Q: Queryable>(
+    conn: &mut Q,
+    tier_id: i32,
+) -> Result<Option<AccountTier>, mysql::Error> {
+    let tier: Option<(i32, String, Decimal)> = conn.exec_first(
+        "SELECT id, name, discount_percent FROM membership_tiers WHERE id = :tier_id",
+        params! { "tier_id" => tier_id },
+    )?;
+    Ok(tier.map(|(id, name, discount_percent)| AccountTier {
+        id,
+        name,
+        discount_percent,
+    }))
+}
+
+/// Moves one customer to the tier their recent spend qualifies for.
+pub fn recompute_tier<Q: Queryable>(conn: &mut Q, user_id: i64) -> Result<(), mysql::Error> {
+    conn.exec_drop(
+        format!(
+            "UPDATE account SET tier_id = {} WHERE id = :user_id AND user_type = 1",
+            TIER_FOR_ACCOUNT
+        ),
+        params! { "user_id" => user_id, "since" => window_start() },
+    )
+}
+
+/// Recomputes every customer's tier.
+pub fn recompute_all_tiers<Q: Queryable>(conn: &mut Q) -> Result<(), mysql::Error> {
+    conn.exec_drop(
+        format!(
+            "UPDATE account SET tier_id = {} WHERE user_type = 1",
+            TIER_FOR_ACCOUNT
+        ),
+        params! { "since" => window_start() },
+    )
+}
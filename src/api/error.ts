@@ -0,0 +1,23 @@
+// Every backend command rejects with this shape (see src-tauri/src/error.rs).
+export interface CafeHubError {
+  code: string; // stable, e.g. "OUT_OF_STOCK", "INSUFFICIENT_BALANCE"
+  message: string;
+  message_zh: string;
+  details: Record<string, unknown> | null;
+}
+
+export const isCafeHubError = (error: unknown): error is CafeHubError => {
+  return typeof error === 'object' && error !== null && 'code' in error && 'message_zh' in error;
+};
+
+// Converts whatever invoke rejected with into an Error with a displayable message.
+export const toError = (error: unknown, fallback: string): Error => {
+  if (isCafeHubError(error)) {
+    return new Error(error.message_zh);
+  } else if (typeof error === 'string') {
+    return new Error(error);
+  } else if (error instanceof Error) {
+    return error;
+  }
+  return new Error(fallback);
+};
@@ -1,4 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
+import { isCafeHubError, toError } from "./error";
+import { getSessionToken } from "./session";
 
 export interface Message {
   id: number;
@@ -32,7 +34,7 @@ export interface CustomerSendMessagePayload {
 
 export const fetchReceivedMessages = async (userId: number): Promise<Message[]> => {
   try {
-    const messages = await invoke<Message[]>("get_recived_messages", { userId });
+    const messages = await invoke<Message[]>("get_recived_messages", { token: getSessionToken(), userId });
     return messages;
   } catch (error) {
     console.error("Failed to fetch received messages:", error);
@@ -42,7 +44,7 @@ export const fetchReceivedMessages = async (userId: number): Promise<Message[]>
 
 export const fetchSentMessages = async (userId: number): Promise<Message[]> => {
   try {
-    const messages = await invoke<Message[]>("get_sent_messages", { userId });
+    const messages = await invoke<Message[]>("get_sent_messages", { token: getSessionToken(), userId });
     return messages;
   } catch (error) {
     console.error("Failed to fetch sent messages:", error);
@@ -63,20 +65,18 @@ export const markMessageAsReadApi = async (messageId: number, currentUserId: num
   try {
     // The Rust command `mark_message_as_read` expects `data` (MarkReadData) and `current_user_id`.
     const payload: MarkReadPayload = { message_id: messageId };
-    const result = await invoke<number>("mark_message_as_read", {
+    await invoke("mark_message_as_read", {
+      token: getSessionToken(),
       data: payload,
       currentUserId: currentUserId,
     });
-    return result;
+    return 0;
   } catch (error) {
     console.error(`Failed to mark message ${messageId} as read:`, error);
-    if (typeof error === 'string') {
-      throw new Error(error);
-    } else if (error instanceof Error) {
-      throw error;
-    } else {
-      throw new Error("An unknown error occurred while marking the message as read.");
+    if (isCafeHubError(error) && error.code === 'NOT_MESSAGE_RECEIVER') {
+      return 1;
     }
+    throw toError(error, "An unknown error occurred while marking the message as read.");
   }
 };
 
@@ -94,35 +94,12 @@ export const sendMessageApi = async (payload: SendMessagePayload): Promise<numbe
       throw new Error("发送者和接收者不能是同一用户");
     }
     // The backend command is "send_message" and expects the payload wrapped in a "data" field.
-    const result = await invoke<number>("admin_send_message", { data: payload });
-
-    // Backend returns:
-    // Ok(0) -> Message sent successfully
-    // Ok(1) -> Sender not found
-    // Ok(2) -> Receiver not found
-    // Err(string) -> Other errors (which invoke will throw as an error)
-
-    if (result === 0) {
-      return 0; // Success
-    } else if (result === 1) {
-      throw new Error("发送方用户不存在。");
-    } else if (result === 2) {
-      throw new Error("接收方用户不存在。");
-    } else {
-      // This case should ideally not be reached if backend strictly returns 0, 1, or 2 for Ok variants.
-      throw new Error(`发送消息时发生未知服务端状态码: ${result}`);
-    }
+    await invoke("admin_send_message", { token: getSessionToken(), data: payload });
+    return 0; // Success
   } catch (error) {
     console.error("Failed to send message:", error);
-    if (typeof error === 'string') {
-      // This can be an error message from invoke if the command itself fails (e.g., command not found)
-      // or a string error from the Rust Err(String)
-      throw new Error(error);
-    } else if (error instanceof Error) {
-      throw error; // Rethrow errors from validation or specific error cases above
-    } else {
-      throw new Error("发送消息时发生未知网络或系统错误。");
-    }
+    // 发送方/接收方不存在等情况由后端以 CafeHubError 返回
+    throw toError(error, "发送消息时发生未知网络或系统错误。");
   }
 };
 
@@ -134,31 +111,10 @@ export const customerSendMessageApi = async (payload: CustomerSendMessagePayload
     // Title can be optional, backend handles Option<String>
     // Backend's customer_send_message determines admin receiver and validates sender.
 
-    const result = await invoke<number>("customer_send_message", { data: payload });
-
-    // Backend returns:
-    // Ok(0) -> Message sent successfully
-    // Ok(1) -> Sender (customer) not found
-    // Ok(2) -> Administrator (receiver) not found
-    // Err(string) -> Other errors
-
-    if (result === 0) {
-      return 0; // Success
-    } else if (result === 1) {
-      throw new Error("发送方用户不存在或非客户账户。");
-    } else if (result === 2) {
-      throw new Error("管理员账户未找到，无法发送消息。");
-    } else {
-      throw new Error(`发送消息时发生未知服务端状态码: ${result}`);
-    }
+    await invoke("customer_send_message", { token: getSessionToken(), data: payload });
+    return 0; // Success
   } catch (error) {
     console.error("Failed to send customer message:", error);
-    if (typeof error === 'string') {
-      throw new Error(error);
-    } else if (error instanceof Error) {
-      throw error;
-    } else {
-      throw new Error("发送客户消息时发生未知网络或系统错误。");
-    }
+    throw toError(error, "发送客户消息时发生未知网络或系统错误。");
   }
 };
\ No newline at end of file
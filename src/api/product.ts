@@ -1,31 +1,81 @@
 import { invoke } from "@tauri-apps/api/core";
+import { isCafeHubError, toError } from "./error";
+import { getSessionToken } from "./session";
 
 // Defines the structure of a product object used in the frontend
 export interface Product {
   id: number;
   goods_name: string;
-  goods_type: string;
+  goods_type: string; // 类别名称
+  category_id?: number | null;
   price: number;
   stock: number;
+  is_active: boolean; // false 表示已下架
 }
 
 // Defines the data structure for updating product information via the API.
 // Matches the `UpdateGoodsData` struct in the Rust backend.
 export interface UpdateProductData {
+  goods_name?: string; // Optional: new name, unique within its category
+  category_id?: number | null; // Optional: move to another existing category, or null for none
   stock?: number; // Optional: new stock quantity
-  price?: number; // Optional: new price
+  price?: number; // Optional: new price, 0 or more
 }
 
 export interface AddProductData {
   goods_name: string;
-  goods_type?: string; // Optional, as in Rust
+  category_id?: number; // Optional, as in Rust
   price: number;
   stock?: number;      // Optional, as in Rust
 }
 
+// Matches the `GoodsCategory` struct in Rust
+export interface GoodsCategory {
+  id: number;
+  name: string;
+  display_order: number;
+  icon?: string | null;
+  is_active: boolean;
+}
+
+// Matches the `GoodsQuery` struct in Rust
+export interface ProductQuery {
+  search?: string; // Matched anywhere in the name
+  category_id?: number;
+  min_price?: number;
+  max_price?: number; // Inclusive
+  in_stock_only?: boolean;
+  include_archived?: boolean; // Needs manage_goods
+  sort_by?: "id" | "name" | "price" | "stock" | "category";
+  descending?: boolean;
+  page?: number; // 1-based
+  page_size?: number;
+}
+
+// Matches the `GoodsPage` struct in Rust
+export interface ProductPage {
+  goods: Product[];
+  total: number; // All matches, not just this page
+  page: number;
+  page_size: number;
+}
+
+// Matches the `GoodsVariant` struct in Rust
+export interface GoodsVariant {
+  id: number;
+  goods_id: number;
+  variant_name: string; // e.g. 大杯 / 冰
+  price_delta: number; // Added to the product's price
+  sku?: string | null;
+  stock?: number | null; // null shares the product's stock
+  display_order: number;
+  is_active: boolean;
+}
+
 // Matches the `PurchaseItem` struct in Rust
 export interface PurchaseItemData {
   goods_id: number; // Changed from i32 to number for TypeScript
+  variant_id?: number; // Optional: must belong to goods_id
   quantity: number; // Changed from i32 to number for TypeScript
 }
 
@@ -41,7 +91,7 @@ export interface PurchaseGoodsPayload {
  */
 export const getProducts = async (): Promise<Product[]> => {
   try {
-    const products = await invoke<Product[]>("get_all_goods");
+    const products = await invoke<Product[]>("get_all_goods", { token: getSessionToken() });
     return products;
   } catch (error) {
     console.error("Failed to fetch products:", error);
@@ -51,6 +101,47 @@ export const getProducts = async (): Promise<Product[]> => {
   }
 };
 
+/**
+ * Searches the catalogue with filters, sorting and paging.
+ * @param query Filters to apply; omitted fields are not filtered on.
+ * @returns A promise that resolves to one page of matches plus the total count.
+ */
+export const searchProducts = async (query: ProductQuery): Promise<ProductPage> => {
+  try {
+    return await invoke<ProductPage>("search_goods", { token: getSessionToken(), data: query });
+  } catch (error) {
+    console.error("Failed to search products:", error);
+    throw toError(error, "An unknown error occurred while searching products.");
+  }
+};
+
+/**
+ * Fetches the active goods categories in menu order.
+ * @returns A promise that resolves to an array of GoodsCategory objects.
+ */
+export const getCategories = async (): Promise<GoodsCategory[]> => {
+  try {
+    return await invoke<GoodsCategory[]>("get_goods_categories", { token: getSessionToken() });
+  } catch (error) {
+    console.error("Failed to fetch categories:", error);
+    return [];
+  }
+};
+
+/**
+ * Fetches the active variants of a product in menu order.
+ * @param goodsId The ID of the product.
+ * @returns A promise that resolves to an array of GoodsVariant objects.
+ */
+export const getVariants = async (goodsId: number): Promise<GoodsVariant[]> => {
+  try {
+    return await invoke<GoodsVariant[]>("get_goods_variants", { token: getSessionToken(), goodsId });
+  } catch (error) {
+    console.error(`Failed to fetch variants for product ${goodsId}:`, error);
+    return [];
+  }
+};
+
 /**
  * Updates the information (stock and/or price) of a specific product on the backend.
  * @param goodsId The ID of the product to update.
@@ -66,6 +157,7 @@ export const AdminUpdateProduct = async (
     // Construct the payload to match the Rust function's arguments.
     // The Rust command `update_goods_info` expects `goods_id` and `data`.
     const payload = {
+      token: getSessionToken(),
       goodsId: goodsId,
       data: data, // `data` here directly matches the `UpdateGoodsData` Rust struct
     };
@@ -81,13 +173,7 @@ export const AdminUpdateProduct = async (
   } catch (error) {
     console.error(`Failed to update product ${goodsId}:`, error);
     // Standardize error throwing to ensure consistency for the caller
-    if (typeof error === 'string') {
-      throw new Error(error);
-    } else if (error instanceof Error) {
-      throw error;
-    } else {
-      throw new Error("An unknown error occurred while updating the product.");
-    }
+    throw toError(error, "An unknown error occurred while updating the product.");
   }
 };
 
@@ -100,18 +186,52 @@ export const AdminUpdateProduct = async (
 export const addProduct = async (data: AddProductData): Promise<string> => {
   try {
     // The Rust command `add_goods` expects `data` as its argument.
-    const result = await invoke<string>("add_goods", { data });
+    const result = await invoke<string>("add_goods", { token: getSessionToken(), data });
     return result; // Return the success message from the backend
   } catch (error) {
     console.error(`Failed to add product:`, error);
     // Standardize error throwing to ensure consistency for the caller
-    if (typeof error === 'string') {
-      throw new Error(error);
-    } else if (error instanceof Error) {
-      throw error;
-    } else {
-      throw new Error("An unknown error occurred while adding the product.");
-    }
+    throw toError(error, "An unknown error occurred while adding the product.");
+  }
+};
+
+/**
+ * Takes a product off the menu. Its order history is kept.
+ * @param goodsId The ID of the product to archive.
+ */
+export const archiveProduct = async (goodsId: number): Promise<void> => {
+  try {
+    await invoke("archive_goods", { token: getSessionToken(), goodsId });
+  } catch (error) {
+    console.error(`Failed to archive product ${goodsId}:`, error);
+    throw toError(error, "An unknown error occurred while archiving the product.");
+  }
+};
+
+/**
+ * Puts an archived product back on the menu.
+ * @param goodsId The ID of the product to restore.
+ */
+export const restoreProduct = async (goodsId: number): Promise<void> => {
+  try {
+    await invoke("restore_goods", { token: getSessionToken(), goodsId });
+  } catch (error) {
+    console.error(`Failed to restore product ${goodsId}:`, error);
+    throw toError(error, "An unknown error occurred while restoring the product.");
+  }
+};
+
+/**
+ * Permanently deletes a product. Rejects with GOODS_IN_USE if any order or
+ * coupon references it; archive it instead.
+ * @param goodsId The ID of the product to delete.
+ */
+export const deleteProduct = async (goodsId: number): Promise<void> => {
+  try {
+    await invoke("delete_goods", { token: getSessionToken(), goodsId });
+  } catch (error) {
+    console.error(`Failed to delete product ${goodsId}:`, error);
+    throw toError(error, "An unknown error occurred while deleting the product.");
   }
 };
 
@@ -128,16 +248,15 @@ export const purchaseGoods = async (payload: PurchaseGoodsPayload): Promise<numb
   try {
     // The Rust command `purchase_goods` expects `data` as its argument,
     // which corresponds to the `PurchaseGoodsData` struct.
-    const result = await invoke<number>("purchase_goods", { data: payload });
-    return result;
+    await invoke("purchase_goods", { token: getSessionToken(), data: payload });
+    return 0;
   } catch (error) {
     console.error(`Failed to purchase goods for user ${payload.user_id}:`, error);
-    if (typeof error === 'string') {
-      throw new Error(error);
-    } else if (error instanceof Error) {
-      throw error;
-    } else {
-      throw new Error("An unknown error occurred during the purchase process.");
+    if (isCafeHubError(error) && error.code === 'OUT_OF_STOCK') {
+      return 1;
+    } else if (isCafeHubError(error) && error.code === 'INSUFFICIENT_BALANCE') {
+      return 2;
     }
+    throw toError(error, "An unknown error occurred during the purchase process.");
   }
 };
\ No newline at end of file
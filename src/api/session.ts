@@ -0,0 +1,15 @@
+// The backend resolves the acting user from this token, so every command
+// except login/register/setup must receive it.
+const SESSION_TOKEN_KEY = 'sessionToken';
+
+export const getSessionToken = (): string => {
+  return localStorage.getItem(SESSION_TOKEN_KEY) ?? '';
+};
+
+export const setSessionToken = (token: string) => {
+  localStorage.setItem(SESSION_TOKEN_KEY, token);
+};
+
+export const clearSessionToken = () => {
+  localStorage.removeItem(SESSION_TOKEN_KEY);
+};
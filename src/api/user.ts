@@ -1,4 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
+import { toError } from "./error";
+import { clearSessionToken, getSessionToken, setSessionToken } from "./session";
 
 export interface Account {
   id: number;
@@ -7,18 +9,39 @@ export interface Account {
   gender?: number | null;
   join_time?: string | null;
   balance?: number | null;
-  user_type: number;
+  points?: number;
+  tier?: AccountTier | null; // customers only
+  role: AccountRole;
+}
+
+export interface AccountTier {
+  id: number;
+  name: string; // e.g. "standard", "silver", "gold", "platinum"
+  discount_percent: number;
+}
+
+export interface AccountRole {
+  id: number;
+  name: string; // e.g. "manager", "cashier", "barista", "customer"
+  is_staff: boolean;
+}
+
+export interface LoginResponse {
+  token: string;
+  expires_in_secs: number;
+  account: Account;
 }
 
 export const login = async (uname: string, pwd: string): Promise<Account | null> => {
   try {
-    // 后端的 "login" tauri 命令应该直接返回 Account 结构体或者在错误时抛出异常
-    const account = await invoke<Account>("login", {
+    // 后端的 "login" tauri 命令返回会话令牌和 Account 结构体，或者在错误时抛出异常
+    const res = await invoke<LoginResponse>("login", {
       username: uname,
       password: pwd,
     });
-    // 如果 invoke 成功，它会返回 Account 对象
-    return account;
+    // 保存会话令牌，之后的所有命令都需要携带它
+    setSessionToken(res.token);
+    return res.account;
   } catch (error) {
     // 如果 invoke 失败 (例如后端返回 Err 或 tauri 通信错误)，会进入 catch 块
     console.error("Login API call failed:", error);
@@ -26,9 +49,19 @@ export const login = async (uname: string, pwd: string): Promise<Account | null>
   }
 };
 
+export const logout = async () => {
+  try {
+    await invoke("logout", { token: getSessionToken() });
+  } catch (error) {
+    console.error("Logout API call failed:", error);
+  } finally {
+    clearSessionToken();
+  }
+};
+
 export const register = async (uname: string, pwd: string, phe: string, gen: number) => {
   try {
-    let res = await invoke("register_user", {
+    await invoke("register_user", {
       data: {
         username: uname,
         password: pwd,
@@ -36,32 +69,24 @@ export const register = async (uname: string, pwd: string, phe: string, gen: num
         gender: gen, // Or pass actual gender if available (0 for Male, 1 for Female)
       },
     });
-    let account: Account | null = null;
-    if (res == 1) {
-      //注册成功
-      account = await login(uname, pwd);
-      return account;
-    } else if (res == 2) {
-      throw new Error('用户名重复');
-    } else if (res == 3) {
-      throw new Error('手机号不是11位');
-    } else if (res == 4) {
-      throw new Error('性别设置错误');
-    }
-    return account;
+    //注册成功
+    return await login(uname, pwd);
   } catch (error) {
     console.error("register failed:", error);
-    throw error;
+    // 用户名重复 / 手机号格式 / 性别错误等都以 CafeHubError 返回
+    throw toError(error, '注册失败，请稍后再试。');
   }
 };
 
 export interface MonthlyConsumptionSummary {
   month: string; // "YYYY-MM"
   total_amount: number;
+  tax_amount: number; // 包含在 total_amount 中
 }
 
 export interface GoodsConsumptionShare {
   goods_name: string;
+  variant_name?: string | null; // 仅在按规格拆分时返回
   amount: number;
 }
 
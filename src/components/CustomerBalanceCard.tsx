@@ -1,23 +1,35 @@
 // src/components/customer/CustomerBalanceCard.tsx
 import React from 'react';
-import { Card, Statistic } from 'antd';
-import { WalletOutlined } from '@ant-design/icons';
+import { Card, Col, Row, Statistic } from 'antd';
+import { GiftOutlined, WalletOutlined } from '@ant-design/icons';
 
 interface CustomerBalanceCardProps {
   balance: number | null | undefined;
+  points?: number;
 }
 
-const CustomerBalanceCard: React.FC<CustomerBalanceCardProps> = ({ balance }) => {
+const CustomerBalanceCard: React.FC<CustomerBalanceCardProps> = ({ balance, points }) => {
   return (
     <Card bordered={false} style={{ marginBottom: '16px' }}>
-      <Statistic
-        title="账户余额"
-        value={balance !== null && balance !== undefined ? balance : 0}
-        precision={2}
-        prefix={<WalletOutlined />}
-        suffix="元"
-        valueStyle={{ color: '#3f8600' }}
-      />
+      <Row gutter={16}>
+        <Col span={12}>
+          <Statistic
+            title="账户余额"
+            value={balance !== null && balance !== undefined ? balance : 0}
+            precision={2}
+            prefix={<WalletOutlined />}
+            suffix="元"
+            valueStyle={{ color: '#3f8600' }}
+          />
+        </Col>
+        <Col span={12}>
+          <Statistic
+            title="积分"
+            value={points ?? 0}
+            prefix={<GiftOutlined />}
+          />
+        </Col>
+      </Row>
     </Card>
   );
 };
@@ -2,6 +2,7 @@
 import React from 'react';
 import { Modal, Form, Input, message } from 'antd';
 import { invoke } from '@tauri-apps/api/core';
+import { getSessionToken } from '@/api/session';
 
 interface CustomerChangePasswordModalProps {
   visible: boolean;
@@ -36,7 +37,8 @@ const CustomerChangePasswordModal: React.FC<CustomerChangePasswordModalProps> =
 
       // 构建传递给 invoke 的 payload，其顶层键名 'data' 和 'userId' 对应 Rust 函数的参数名
       const payloadForInvoke = {
-        userId: userId,                     // 对应 Rust 函数的第一个参数 userId
+        token: getSessionToken(),           // 后端根据会话令牌确定当前用户
+        userId: userId,
         data: updatePasswordPayloadForRust  // 对应 Rust 函数的第二个参数 data
       };
 
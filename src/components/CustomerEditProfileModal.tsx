@@ -2,6 +2,7 @@
 import React, { useEffect } from 'react';
 import { Modal, Form, Input, Select, message } from 'antd';
 import { invoke } from '@tauri-apps/api/core';
+import { getSessionToken } from '@/api/session';
 import type { Account } from '@/api/user';
 
 const { Option } = Select;
@@ -95,6 +96,7 @@ const CustomerEditProfileModal: React.FC<CustomerEditProfileModalProps> = ({
     setLoading(true);
     try {
       await invoke<string>('update_user_details', {
+        token: getSessionToken(),
         userId: currentUser.id,
         data: payload, // data 现在可能包含 username, phone, gender
       });
@@ -3,6 +3,7 @@ import React, { useState } from 'react';
 import { Card, InputNumber, Button, Form, message, Typography } from 'antd';
 import { PropertySafetyOutlined, DollarCircleOutlined } from '@ant-design/icons';
 import { invoke } from '@tauri-apps/api/core';
+import { getSessionToken } from '@/api/session';
 
 // 如果 Account 类型也包含 balance，可以复用，否则定义一个简单类型
 // interface AccountForBalance {
@@ -35,35 +36,27 @@ const CustomerRechargeCard: React.FC<CustomerRechargeCardProps> = ({ userId, cur
       const rechargeDataForRust = {
         user_id: userId,       // 确保字段名与 Rust 结构体中的 user_id 匹配 (snake_case)
         amount: values.amount, // 字段名 amount 与 Rust 结构体匹配
+        payment_method: 'cash', // cash / card / transfer
       };
 
       // 2. 构建传递给 invoke 的 payload，其顶层键名 'data' 对应 Rust 函数的参数名
       const payloadForInvoke = {
+        token: getSessionToken(),
         data: rechargeDataForRust
       };
 
       // 调用后端的 recharge_balance 命令
-      const resultMessage = await invoke<string>('recharge_balance', payloadForInvoke);
+      const result = await invoke<{ balance_after: number; bonus_amount: number }>('recharge_balance', payloadForInvoke);
 
-      message.success(resultMessage || '充值成功！');
+      message.success(Number(result.bonus_amount) > 0 ? `充值成功！赠送 ${Number(result.bonus_amount).toFixed(2)} 元` : '充值成功！');
       form.resetFields(); // 清空表单
 
-      // 更新余额的逻辑：
-      // 将 currentBalance 安全地转换为数字
-      const numericCurrentBalance = (currentBalance === null || currentBalance === undefined || currentBalance === '')
-        ? 0
-        : Number(currentBalance);
-
-      if (isNaN(numericCurrentBalance)) {
-        console.warn("Current balance was NaN after conversion. Using 0 for new balance calculation.");
-        onRechargeSuccess(values.amount); // 如果当前余额无效，则新余额就是充值金额
-      } else {
-        onRechargeSuccess(numericCurrentBalance + values.amount);
-      }
+      // 后端返回充值后的余额
+      onRechargeSuccess(result.balance_after);
 
     } catch (error: any) {
       console.error('充值失败 (CustomerRechargeCard):', error);
-      const errorMessage = typeof error === 'string' ? error : (error?.message || '充值操作失败，请稍后再试。');
+      const errorMessage = typeof error === 'string' ? error : (error?.message_zh || error?.message || '充值操作失败，请稍后再试。');
       message.error(errorMessage);
     } finally {
       setLoading(false);
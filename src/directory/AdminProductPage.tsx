@@ -1,4 +1,4 @@
-import { AdminUpdateProduct, getProducts } from '@/api/product';
+import { AdminUpdateProduct, getCategories, getProducts, GoodsCategory } from '@/api/product';
 import AddGoodsBtn from '@/components/AddGoodsBtn';
 import EditGoodsBtn from '@/components/EditGoodsBtn';
 import { Table, Space, TableProps, message } from 'antd';
@@ -14,12 +14,9 @@ interface Product {
   goods_type: string;
 }
 
-// 模拟商品类别
-const initialCategories = ['咖啡类', '非咖啡饮品', '烘焙食品', '轻食简餐', '咖啡豆与周边'];
-
 const AdminProductPage = () => {
   const [products, setProducts] = useState<Product[]>([]); // 商品列表
-  const [categories] = useState<string[]>(initialCategories);
+  const [categories, setCategories] = useState<GoodsCategory[]>([]);
   const [loading, setLoading] = useState<boolean>(true); // 添加加载状态
 
   const handleEditProduct = async (updatedProduct: Product) => {
@@ -69,6 +66,7 @@ const AdminProductPage = () => {
   // 使用useEffect在组件挂载时获取商品数据
   useEffect(() => {
     fetchProducts();
+    getCategories().then(setCategories);
   }, []); // 空依赖数组意味着这个effect只会在组件挂载时运行一次
 
   const columns: TableProps<Product>['columns'] = [
@@ -96,10 +94,10 @@ const AdminProductPage = () => {
       title: '类别',
       dataIndex: 'goods_type',
       key: 'goods_type',
-      filters: categories.map(goods_type => ({ text: goods_type, value: goods_type })),
+      filters: categories.map(category => ({ text: category.name, value: category.name })),
       onFilter: (value: React.Key | boolean, record: Product) => {
         if (typeof value === 'string') {
-          return record.goods_type.includes(value);
+          return record.goods_type === value;
         }
         return false;
       },
@@ -135,7 +133,7 @@ const AdminProductPage = () => {
   return (
     <div style={{ padding: '20px' }}>
       <AddGoodsBtn
-        initCategories={initialCategories}
+        initCategories={categories}
         onProductAdded={fetchProducts}
       />
 
@@ -118,7 +118,7 @@ const CustomerInfoPage: React.FC = () => {
           />
         </Col>
         <Col xs={24} md={12}>
-          <CustomerBalanceCard balance={account?.balance} />
+          <CustomerBalanceCard balance={account?.balance} points={account?.points} />
           <CustomerConsumptionChart data={monthlyConsumption} />
         </Col>
       </Row>
@@ -32,7 +32,7 @@ const CustomerMessagePage = () => {
     if (storedAccountString) {
       try {
         const storedAccount: Account = JSON.parse(storedAccountString);
-        if (storedAccount.user_type !== 1) { // Ensure it's a customer (user_type 1)
+        if (storedAccount.role?.is_staff !== false) { // Ensure it's a customer role
           AntMessage.error('您不是客户账户，无法访问此页面。');
           // Potentially redirect or disable functionality
           return;
@@ -25,17 +25,10 @@ const LoginPage = () => {
         });
         localStorage.setItem('loginAccount', JSON.stringify(account));
         localStorage.setItem('isAuthenticated', 'true');
-        if (account.user_type === 0) {
+        if (account.role.is_staff) {
           navigate('/admin');
-        } else if (account.user_type === 1) {
-          navigate('/customer');
         } else {
-          console.error("Unknown user type:", account.user_type);
-          messageApi.open({
-            type: 'error',
-            content: '未知的用户类型',
-            duration: 2,
-          });
+          navigate('/customer');
         }
       } else {
         console.error('登录失败 (API返回null或发生错误)');
@@ -39,7 +39,7 @@ const RegisterPage = () => {
       });
       localStorage.setItem('loginAccount', JSON.stringify(account));
       localStorage.setItem('isAuthenticated', 'true');
-      if (account != null && account.user_type === 1) {
+      if (account != null && !account.role.is_staff) {
         navigate('/customer');
       }
     } catch (error) {